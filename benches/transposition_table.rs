@@ -0,0 +1,94 @@
+//! Transposition Table Benchmark
+//!
+//! Compares the hit rates and speed of a direct-mapped transposition table and set-associative tables of the same size.
+//!
+//! The workload imitates a search: the fingerprints are drawn from a working set several times the size of the table, with
+//! some states referenced much more often than others, and the states referenced most often have the highest qualities. A
+//! state that is not found is stored. The table is aged periodically, as it would be between moves.
+//!
+//! Run with `cargo bench --bench transposition_table`.
+
+use std::time::Instant;
+
+use game_player::transposition_table::TranspositionTable;
+
+const TABLE_SIZE: usize = 1 << 16;
+const WORKING_SET: u64 = TABLE_SIZE as u64 * 4;
+const LOOKUPS: usize = 4_000_000;
+const LOOKUPS_PER_AGE: usize = 100_000;
+const DEEP_QUALITY: i16 = 8;
+
+// A xorshift pseudo-random number generator, so that every table sees the same sequence
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+// Scrambles a state number into a fingerprint
+fn fingerprint(state: u64) -> u64 {
+    let mut x = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+struct Results {
+    hits: usize,
+    deep_lookups: usize,
+    deep_hits: usize,
+    seconds: f64,
+}
+
+fn run(mut table: TranspositionTable) -> Results {
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+    let mut results = Results {
+        hits: 0,
+        deep_lookups: 0,
+        deep_hits: 0,
+        seconds: 0.0,
+    };
+
+    let start = Instant::now();
+    for i in 0..LOOKUPS {
+        // Low-numbered states are referenced more often, and have higher qualities
+        let state = random.next() % (random.next() % WORKING_SET + 1);
+        let quality = (DEEP_QUALITY as u64 * 2 * (WORKING_SET - state) / WORKING_SET) as i16;
+        let deep = quality >= DEEP_QUALITY;
+        results.deep_lookups += usize::from(deep);
+
+        let fingerprint = fingerprint(state);
+        if table.check(fingerprint, -1).is_some() {
+            results.hits += 1;
+            results.deep_hits += usize::from(deep);
+        } else {
+            table.update(fingerprint, state as f32, quality);
+        }
+
+        if (i + 1) % LOOKUPS_PER_AGE == 0 {
+            table.age();
+        }
+    }
+    results.seconds = start.elapsed().as_secs_f64();
+    results
+}
+
+fn main() {
+    println!("{TABLE_SIZE} entries, {WORKING_SET} states, {LOOKUPS} lookups");
+    println!("{:>6} {:>10} {:>15} {:>12}", "ways", "hit rate", "deep hit rate", "ns/lookup");
+    for ways in [1, 2, 4, 8] {
+        let results = run(TranspositionTable::with_ways(TABLE_SIZE, ways, 8));
+        println!(
+            "{:>6} {:>9.2}% {:>14.2}% {:>12.1}",
+            ways,
+            100.0 * results.hits as f64 / LOOKUPS as f64,
+            100.0 * results.deep_hits as f64 / results.deep_lookups.max(1) as f64,
+            results.seconds * 1e9 / LOOKUPS as f64
+        );
+    }
+}
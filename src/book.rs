@@ -0,0 +1,280 @@
+//! Opening Book
+//!
+//! This module implements an opening book, which maps states to weighted candidate responses, so that the responses to
+//! well-known states do not need to be searched. States and responses are identified by their fingerprints (see
+//! [`State::fingerprint`]).
+//!
+//! A book is typically built from game records with [`Book::add_game`], saved with [`Book::save`], and loaded by the player
+//! with [`Book::load`]. The player then uses [`search`] in place of [`minimax::search`](crate::minimax::search).
+//!
+//! # Book Format
+//! A book is saved as the bytes `GPBK`, a 32-bit format version and the 64-bit number of states, followed by each state. A
+//! state is its 64-bit fingerprint, the 32-bit number of candidates, and each candidate's 64-bit fingerprint and 32-bit
+//! weight. All values are little-endian, and the states are in order of fingerprint.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use game_player::book::{self, Book};
+//!
+//! // Build a book from the first 10 plies of the recorded games
+//! let mut opening_book = Book::new();
+//! for record in &game_records {
+//!     opening_book.add_game(&initial_state, &record.actions, 10);
+//! }
+//! opening_book.save("openings.book")?;
+//!
+//! // Later...
+//! let opening_book = Book::load("openings.book")?;
+//! let random = my_rng.random::<f64>();
+//! let best_move = book::search(&opening_book, &tt, &evaluator, &move_generator, &game_state, 6, random);
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::minimax::{self, ResponseGenerator};
+use crate::score::Score;
+use crate::state::*;
+use crate::static_evaluator::*;
+use crate::transposition_table::*;
+
+/// A candidate response in the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate {
+    /// The fingerprint of the response
+    pub response: u64,
+    /// The weight of the response. The probability of choosing it is its weight divided by the total weight of the
+    /// candidates.
+    pub weight: u32,
+}
+
+/// An opening book.
+///
+/// # Examples
+/// ```rust
+/// # use game_player::book::Book;
+/// let mut book = Book::new();
+/// book.add(1, 2, 3);
+/// book.add(1, 4, 1);
+///
+/// // 2 is chosen 3 times as often as 4
+/// assert_eq!(book.choose(1, 0.5), Some(2));
+/// assert_eq!(book.choose(1, 0.8), Some(4));
+/// assert_eq!(book.choose(5, 0.5), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Book {
+    states: HashMap<u64, Vec<Candidate>>,
+}
+
+const MAGIC: &[u8; 4] = b"GPBK";
+const VERSION: u32 = 1;
+
+impl Book {
+    /// Creates an empty book.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of states in the book.
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Returns true if the book has no states.
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Adds a candidate response to a state. If the response is already a candidate, the weight is added to its weight.
+    ///
+    /// # Arguments
+    /// * `fingerprint` - The fingerprint of the state
+    /// * `response` - The fingerprint of the response
+    /// * `weight` - The weight of the response
+    pub fn add(&mut self, fingerprint: u64, response: u64, weight: u32) {
+        let candidates = self.states.entry(fingerprint).or_default();
+        match candidates.iter_mut().find(|candidate| candidate.response == response) {
+            Some(candidate) => candidate.weight = candidate.weight.saturating_add(weight),
+            None => candidates.push(Candidate { response, weight }),
+        }
+    }
+
+    /// Adds the responses in a game record to the book. Each response adds 1 to its weight.
+    ///
+    /// # Arguments
+    /// * `s0` - The state at the start of the game
+    /// * `actions` - The actions of the game, in order
+    /// * `max_plies` - The number of actions added, at most
+    pub fn add_game<S: State>(&mut self, s0: &S, actions: &[S::Action], max_plies: usize) {
+        let mut fingerprint = s0.fingerprint();
+        let mut state = None;
+        for action in actions.iter().take(max_plies) {
+            let next = state.as_ref().unwrap_or(s0).apply(action);
+            let response = next.fingerprint();
+            self.add(fingerprint, response, 1);
+            fingerprint = response;
+            state = Some(next);
+        }
+    }
+
+    /// Returns the candidate responses to a state.
+    ///
+    /// # Arguments
+    /// * `fingerprint` - The fingerprint of the state
+    ///
+    /// # Returns
+    /// The candidates, which is empty if the state is not in the book
+    pub fn candidates(&self, fingerprint: u64) -> &[Candidate] {
+        self.states.get(&fingerprint).map_or(&[], Vec::as_slice)
+    }
+
+    /// Chooses one of the candidate responses to a state at random, in proportion to their weights.
+    ///
+    /// # Arguments
+    /// * `fingerprint` - The fingerprint of the state
+    /// * `random` - A random number in the range [0, 1)
+    ///
+    /// # Returns
+    /// The fingerprint of the chosen response, or `None` if the state is not in the book or all weights are 0
+    pub fn choose(&self, fingerprint: u64, random: f64) -> Option<u64> {
+        let candidates = self.candidates(fingerprint);
+        let total: u64 = candidates.iter().map(|candidate| u64::from(candidate.weight)).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut target = ((random.clamp(0.0, 1.0) * total as f64) as u64).min(total - 1);
+        for candidate in candidates {
+            if target < u64::from(candidate.weight) {
+                return Some(candidate.response);
+            }
+            target -= u64::from(candidate.weight);
+        }
+        None
+    }
+
+    /// Saves the book to a file.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be created or written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Loads a book from a file.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not a book of a supported version.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Writes the book in the book format.
+    ///
+    /// # Errors
+    /// Returns an error if the writer fails.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(self.states.len() as u64).to_le_bytes())?;
+
+        let mut fingerprints: Vec<&u64> = self.states.keys().collect();
+        fingerprints.sort_unstable();
+        for fingerprint in fingerprints {
+            let candidates = &self.states[fingerprint];
+            writer.write_all(&fingerprint.to_le_bytes())?;
+            writer.write_all(&(candidates.len() as u32).to_le_bytes())?;
+            for candidate in candidates {
+                writer.write_all(&candidate.response.to_le_bytes())?;
+                writer.write_all(&candidate.weight.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a book in the book format.
+    ///
+    /// # Errors
+    /// Returns an error if the reader fails or the data is not a book of a supported version.
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not an opening book"));
+        }
+        if read_u32(reader)? != VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "unsupported opening book version"));
+        }
+
+        let count = read_u64(reader)?;
+        let mut book = Self::new();
+        for _ in 0..count {
+            let fingerprint = read_u64(reader)?;
+            for _ in 0..read_u32(reader)? {
+                let response = read_u64(reader)?;
+                let weight = read_u32(reader)?;
+                book.add(fingerprint, response, weight);
+            }
+        }
+        Ok(book)
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// A minimax search that consults an opening book first.
+///
+/// If the book has candidate responses to `s0`, one of them is chosen at random in proportion to the weights, and the
+/// response generated by `rg` with the same fingerprint is returned without a search. Otherwise (or if none of the
+/// generated responses has that fingerprint), the result of [`minimax::search`] is returned.
+///
+/// # Arguments
+/// * `book` - The opening book
+/// * `tt` - A transposition table for caching previously computed positions. Can be reused across multiple searches.
+/// * `sef` - The static evaluation function
+/// * `rg` - The response generator. It is called with a depth of 0 to generate the response chosen from the book.
+/// * `s0` - The state to search from
+/// * `max_depth` - Maximum search depth in plies
+/// * `random` - A random number in the range [0, 1), used to choose a response from the book
+///
+/// # Returns
+/// `Some(Rc<S>)` containing the best move found, or `None` if no valid moves exist
+pub fn search<S, V, E, R>(
+    book: &Book,
+    tt: &Rc<RefCell<TranspositionTable<V>>>,
+    sef: &E,
+    rg: &R,
+    s0: &Rc<S>,
+    max_depth: i32,
+    random: f64,
+) -> Option<Rc<S>>
+where
+    S: State,
+    V: Score,
+    E: StaticEvaluator<S, V>,
+    R: ResponseGenerator<State = S>,
+{
+    if let Some(fingerprint) = book.choose(s0.fingerprint(), random)
+        && let Some(response) = rg.generate(s0, 0).into_iter().find(|response| response.fingerprint() == fingerprint)
+    {
+        return Some(Rc::from(response));
+    }
+    minimax::search(tt, sef, rg, s0, max_depth)
+}
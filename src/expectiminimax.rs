@@ -0,0 +1,713 @@
+//! Expectiminimax Game Tree Search Implementation
+//!
+//! This module implements a game tree search for games with chance events, such as dice rolls and card draws. Alice's and
+//! Bob's choices are searched using the min-max strategy with alpha-beta pruning, and the value of a chance event is the
+//! expected value of its outcomes. Values are cached in a transposition table, just as they are by the
+//! [`minimax`](crate::minimax) search.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use std::cell::RefCell;
+//! use std::rc::Rc;
+//! use crate::expectiminimax::search;
+//! use crate::transposition_table::TranspositionTable;
+//!
+//! // Assuming you have implemented the required traits for your game
+//! let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+//! let static_evaluator = MyStaticEvaluator::new();
+//! let response_generator = MyResponseGenerator::new();
+//! let initial_state = Rc::new(MyGameState::new());
+//!
+//! if let Some(best_move) = search(&tt, &static_evaluator, &response_generator, &initial_state, 6) {
+//!     println!("Best move found: {:?}", best_move);
+//! }
+//! ```
+//!
+//! # Chance Node Pruning
+//! By default, every outcome of a chance event is searched. Ballard's Star1 and Star2 algorithms prune chance nodes using the
+//! range of possible values, [`bob_wins_value`](StaticEvaluator::bob_wins_value) to
+//! [`alice_wins_value`](StaticEvaluator::alice_wins_value), and return the same result. They are enabled with
+//! [`search_with_config`] and [`ChancePruning`]. The tighter the range of values, the more effective the pruning.
+//!
+//! # Notes
+//! - A chance event counts as a ply.
+//! - The state being searched from must not be a chance node, since there is no choice to make.
+//! - Values are `f32`, since the expected value of a chance event is generally not an integer.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::minimax::ResponseGenerator;
+use crate::state::*;
+use crate::static_evaluator::*;
+use crate::transposition_table::*;
+
+static SEF_QUALITY: i16 = 0; // Quality of a value returned by the static evaluation function
+
+/// A trait for states in games with chance events.
+///
+/// A state is either a decision node, in which the player whose turn it is chooses a response, or a chance node, in which
+/// the next state is determined by chance (e.g. a roll of the dice).
+///
+/// # Examples
+///
+/// ```rust
+/// # use game_player::State;
+/// # use game_player::expectiminimax::ChanceState;
+/// # #[derive(Clone)]
+/// # struct Roll;
+/// #[derive(Clone, Copy)]
+/// struct BackgammonState {
+///     dice_rolled: bool,
+///     // other game-specific fields...
+/// }
+///
+/// # impl State for BackgammonState {
+/// #     type Action = Roll;
+/// #     fn fingerprint(&self) -> u64 { self.dice_rolled as u64 }
+/// #     fn whose_turn(&self) -> u8 { 0 }
+/// #     fn is_terminal(&self) -> bool { false }
+/// #     fn apply(&self, _action: &Roll) -> Self { *self }
+/// # }
+/// impl ChanceState for BackgammonState {
+///     fn is_chance_node(&self) -> bool {
+///         // The dice must be rolled before the player can move
+///         !self.dice_rolled
+///     }
+/// }
+/// ```
+pub trait ChanceState: State {
+    /// Returns `true` if the next state is determined by chance rather than by a player's choice.
+    fn is_chance_node(&self) -> bool;
+}
+
+/// A possible outcome of a chance event.
+pub struct Outcome<S> {
+    /// The state resulting from the outcome
+    pub state: Box<S>,
+    /// The probability of the outcome
+    pub probability: f32,
+}
+
+/// Response generator function object trait for games with chance events.
+///
+/// In addition to generating the responses to a decision node (see [`ResponseGenerator`]), this generates the possible
+/// outcomes of a chance node.
+///
+/// # Examples
+/// ```rust,ignore
+/// use std::rc::Rc;
+/// use crate::expectiminimax::{ChanceResponseGenerator, Outcome};
+///
+/// impl ChanceResponseGenerator for MyResponseGenerator {
+///     fn outcomes(&self, state: &Rc<Self::State>, _depth: i32) -> Vec<Outcome<Self::State>> {
+///         // Each face of the die is equally likely
+///         (1..=6)
+///             .map(|face| Outcome { state: Box::new(state.roll(face)), probability: 1.0 / 6.0 })
+///             .collect()
+///     }
+/// }
+/// ```
+pub trait ChanceResponseGenerator: ResponseGenerator {
+    /// Generates a list of all possible outcomes of the chance event in the given state.
+    ///
+    /// # Arguments
+    /// * `state` - The chance node to generate outcomes for
+    /// * `depth` - Current search depth (ply number), useful for optimizations
+    ///
+    /// # Returns
+    /// A vector of outcomes and their probabilities. Returns an empty vector if there are no outcomes.
+    ///
+    /// # Note
+    /// The probabilities are expected to sum to 1. If they do not, they are normalized by the search.
+    fn outcomes(&self, state: &Rc<Self::State>, depth: i32) -> Vec<Outcome<Self::State>>;
+}
+
+// Holds evaluation information about a response or an outcome.
+struct Response<S> {
+    // Reference to the resulting state
+    state: Rc<S>,
+    // Value of the state
+    value: f32,
+    // Quality of the value. Quality is the number of plies searched to find the value.
+    quality: i16,
+}
+
+// Holds static information pertaining to the search.
+struct Context<'a, S, E: StaticEvaluator<S>, R: ChanceResponseGenerator<State = S>>
+where
+    S: ChanceState,
+{
+    max_depth: i32,
+    rg: &'a R,
+    sef: &'a E,
+    tt: &'a Rc<RefCell<TranspositionTable>>,
+    pruning: ChancePruning,
+    _phantom: std::marker::PhantomData<S>,
+}
+
+/// Pruning algorithms for chance nodes.
+///
+/// These are Ballard's *-minimax algorithms. They bound the value of a chance node by assuming that each outcome not yet
+/// searched has the lowest (or highest) possible value, and stop searching the outcomes once the bounds show that the value is
+/// outside the window of values that matter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChancePruning {
+    /// Every outcome is searched.
+    #[default]
+    Disabled,
+    /// The search windows of the outcomes are narrowed by the bounds on the value of the chance node.
+    Star1,
+    /// Star1, preceded by a probing phase that tightens the bounds by searching only the first response to each outcome.
+    /// Outcomes that are chance nodes are not probed. The probes are searched again unless they cause a cutoff, so Star2 is
+    /// only worthwhile if the first response is usually the best.
+    Star2,
+}
+
+/// Search options.
+///
+/// The default configuration does not prune chance nodes.
+///
+/// # Examples
+/// ```rust
+/// # use game_player::expectiminimax::{ChancePruning, Config};
+/// let config = Config { chance_pruning: ChancePruning::Star2 };
+/// assert_ne!(config, Config::default());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    /// The pruning algorithm applied to chance nodes
+    pub chance_pruning: ChancePruning,
+}
+
+/// An expectiminimax search implementation using alpha-beta pruning and a transposition table.
+///
+/// This function searches for the best move for the current player in a game with chance events. The value of a chance
+/// node is the expected value of its outcomes, and the values of Alice's and Bob's choices are determined by min-max search.
+///
+/// # Type Parameters
+/// * `S` - Game state type that implements the `ChanceState` trait
+/// * `E` - Static evaluator type that implements `StaticEvaluator<S>`
+/// * `R` - Response generator type that implements `ChanceResponseGenerator`
+///
+/// # Arguments
+/// * `tt` - A transposition table for caching previously computed positions. Can be reused across multiple searches.
+/// * `sef` - The static evaluation function
+/// * `rg` - The response and outcome generator
+/// * `s0` - The state to search from
+/// * `max_depth` - Maximum search depth in plies, including chance events
+///
+/// # Returns
+/// `Some(Rc<S>)` containing the best move found, or `None` if no valid moves exist or `s0` is a chance node
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+/// use crate::expectiminimax::search;
+/// use crate::transposition_table::TranspositionTable;
+///
+/// let transposition_table = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+/// let evaluator = MyStaticEvaluator::new();
+/// let generator = MyResponseGenerator::new();
+/// let game_state = Rc::new(MyGameState::initial_position());
+///
+/// match search(&transposition_table, &evaluator, &generator, &game_state, 4) {
+///     Some(best_move) => println!("Best move: {:?}", best_move),
+///     None => println!("No moves available"),
+/// }
+/// ```
+pub fn search<S, E, R>(
+    tt: &Rc<RefCell<TranspositionTable>>,
+    sef: &E,
+    rg: &R,
+    s0: &Rc<S>,
+    max_depth: i32,
+) -> Option<Rc<S>>
+where
+    S: ChanceState,
+    E: StaticEvaluator<S>,
+    R: ChanceResponseGenerator<State = S>,
+{
+    search_with_config(tt, sef, rg, s0, max_depth, &Config::default())
+}
+
+/// An expectiminimax search with the given options.
+///
+/// This is the same as [`search`], except that the search is configured by `config`.
+///
+/// # Arguments
+/// * `tt` - A transposition table for caching previously computed positions. Can be reused across multiple searches.
+/// * `sef` - The static evaluation function. Its win values bound the values used by chance node pruning.
+/// * `rg` - The response and outcome generator
+/// * `s0` - The state to search from
+/// * `max_depth` - Maximum search depth in plies, including chance events
+/// * `config` - Search options
+///
+/// # Returns
+/// `Some(Rc<S>)` containing the best move found, or `None` if no valid moves exist or `s0` is a chance node
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use crate::expectiminimax::{search_with_config, ChancePruning, Config};
+///
+/// let config = Config { chance_pruning: ChancePruning::Star2 };
+/// let best_move = search_with_config(&transposition_table, &evaluator, &generator, &game_state, 6, &config);
+/// ```
+pub fn search_with_config<S, E, R>(
+    tt: &Rc<RefCell<TranspositionTable>>,
+    sef: &E,
+    rg: &R,
+    s0: &Rc<S>,
+    max_depth: i32,
+    config: &Config,
+) -> Option<Rc<S>>
+where
+    S: ChanceState,
+    E: StaticEvaluator<S>,
+    R: ChanceResponseGenerator<State = S>,
+{
+    // There is no choice to be made at a chance node
+    if s0.is_chance_node() {
+        return None;
+    }
+
+    let context = Context {
+        tt,
+        sef,
+        rg,
+        max_depth,
+        pruning: config.chance_pruning,
+        _phantom: std::marker::PhantomData,
+    };
+    let response = if s0.whose_turn() == PlayerId::ALICE as u8 {
+        alice_search(&context, s0, -f32::INFINITY, f32::INFINITY, 0)
+    } else {
+        bob_search(&context, s0, -f32::INFINITY, f32::INFINITY, 0)
+    };
+    response.map(|response| response.state)
+}
+
+// Evaluates all of Alice's possible responses to the given state. The returned response is the one with the highest value.
+fn alice_search<S, E, R>(
+    context: &Context<S, E, R>,
+    state: &Rc<S>,
+    mut alpha: f32,
+    beta: f32,
+    depth: i32,
+) -> Option<Response<S>>
+where
+    S: ChanceState,
+    E: StaticEvaluator<S>,
+    R: ChanceResponseGenerator<State = S>,
+{
+    let original_alpha = alpha;
+
+    // Generate a list of the possible responses to this state by Alice. The responses are initialized with preliminary values.
+    let mut responses = generate_responses(context, context.rg.generate(state, depth));
+
+    // If there are no responses, return without a response. It's up to the caller to decide how to handle this case.
+    if responses.is_empty() {
+        return None;
+    }
+
+    // Sort from highest to lowest in order to increase the chance of triggering a beta cutoff earlier.
+    responses.sort_by(|a, b| b.value.total_cmp(&a.value));
+
+    // Evaluate each of the responses and choose the one with the highest value
+    let mut best_state: Option<&Rc<S>> = None;
+    let mut best_value = -f32::INFINITY;
+    let mut best_quality = -1;
+
+    for response in &responses {
+        let (value, quality) = search_response(context, response, alpha, beta, depth + 1);
+
+        if value > best_value {
+            best_state = Some(&response.state);
+            best_value = value;
+            best_quality = quality;
+
+            // If Alice wins with this response, then there is no reason to look for anything better
+            if best_value >= context.sef.alice_wins_value() {
+                break;
+            }
+
+            // Beta cutoff. Bob will not allow this state to be reached.
+            if best_value > beta {
+                break;
+            }
+
+            if best_value > alpha {
+                alpha = best_value;
+            }
+        }
+    }
+
+    // The value of this state is the value of the best response to it. Save it in the T-table only if it is exact.
+    if best_value >= context.sef.alice_wins_value() || is_exact(best_value, original_alpha, beta) {
+        context
+            .tt
+            .borrow_mut()
+            .update_with_key(state.fingerprint(), state.verification_key(), best_value, best_quality + 1);
+    }
+
+    Some(Response::<S> {
+        state: Rc::clone(best_state?),
+        value: best_value,
+        quality: best_quality + 1,
+    })
+}
+
+// Evaluates all of Bob's possible responses to the given state. The returned response is the one with the lowest value.
+fn bob_search<S, E, R>(
+    context: &Context<S, E, R>,
+    state: &Rc<S>,
+    alpha: f32,
+    mut beta: f32,
+    depth: i32,
+) -> Option<Response<S>>
+where
+    S: ChanceState,
+    E: StaticEvaluator<S>,
+    R: ChanceResponseGenerator<State = S>,
+{
+    let original_beta = beta;
+
+    // Generate a list of the possible responses to this state by Bob. The responses are initialized with preliminary values.
+    let mut responses = generate_responses(context, context.rg.generate(state, depth));
+
+    // If there are no responses, return without a response. It's up to the caller to decide how to handle this case.
+    if responses.is_empty() {
+        return None;
+    }
+
+    // Sort from lowest to highest in order to increase the chance of triggering an alpha cutoff earlier
+    responses.sort_by(|a, b| a.value.total_cmp(&b.value));
+
+    // Evaluate each of the responses and choose the one with the lowest value
+    let mut best_state: Option<&Rc<S>> = None;
+    let mut best_value = f32::INFINITY;
+    let mut best_quality = -1;
+
+    for response in &responses {
+        let (value, quality) = search_response(context, response, alpha, beta, depth + 1);
+
+        if value < best_value {
+            best_state = Some(&response.state);
+            best_value = value;
+            best_quality = quality;
+
+            // If Bob wins with this response, then there is no reason to look for anything better
+            if best_value <= context.sef.bob_wins_value() {
+                break;
+            }
+
+            // Alpha cutoff. Alice will not allow this state to be reached.
+            if best_value < alpha {
+                break;
+            }
+
+            if best_value < beta {
+                beta = best_value;
+            }
+        }
+    }
+
+    // The value of this state is the value of the best response to it. Save it in the T-table only if it is exact.
+    if best_value <= context.sef.bob_wins_value() || is_exact(best_value, alpha, original_beta) {
+        context
+            .tt
+            .borrow_mut()
+            .update_with_key(state.fingerprint(), state.verification_key(), best_value, best_quality + 1);
+    }
+
+    Some(Response::<S> {
+        state: Rc::clone(best_state?),
+        value: best_value,
+        quality: best_quality + 1,
+    })
+}
+
+// Computes the expected value of the outcomes of the chance event in the given state. Returns the value and its quality, or
+// None if there are no outcomes. If the outcomes are pruned, the returned value is a bound outside of the window.
+fn chance_search<S, E, R>(
+    context: &Context<S, E, R>,
+    state: &Rc<S>,
+    alpha: f32,
+    beta: f32,
+    depth: i32,
+) -> Option<(f32, i16)>
+where
+    S: ChanceState,
+    E: StaticEvaluator<S>,
+    R: ChanceResponseGenerator<State = S>,
+{
+    let outcomes = context.rg.outcomes(state, depth);
+    let total_probability: f32 = outcomes.iter().map(|outcome| outcome.probability).sum();
+    if outcomes.is_empty() || total_probability <= 0.0 {
+        return None;
+    }
+
+    // Outcomes that cannot occur do not contribute to the value
+    let outcomes: Vec<(Response<S>, f32)> = outcomes
+        .into_iter()
+        .filter(|outcome| outcome.probability > 0.0)
+        .map(|outcome| {
+            let probability = outcome.probability / total_probability;
+            (preliminary_response(context, outcome.state), probability)
+        })
+        .collect();
+
+    // Bounds on the value of each outcome. The value of an outcome that will not be searched is already known.
+    let mut lower: Vec<f32> = outcomes
+        .iter()
+        .map(|(response, _)| {
+            if needs_search(context, response, depth + 1) {
+                context.sef.bob_wins_value()
+            } else {
+                response.value
+            }
+        })
+        .collect();
+    let mut upper: Vec<f32> = outcomes
+        .iter()
+        .zip(&lower)
+        .map(|((response, _), &lower)| {
+            if needs_search(context, response, depth + 1) {
+                context.sef.alice_wins_value()
+            } else {
+                lower
+            }
+        })
+        .collect();
+
+    if context.pruning == ChancePruning::Star2
+        && let Some(bound) = probe_outcomes(context, &outcomes, &mut lower, &mut upper, alpha, beta, depth)
+    {
+        return Some((bound, SEF_QUALITY));
+    }
+
+    // The value of a chance node is the probability-weighted sum of the values of its outcomes, and its quality is limited
+    // by the outcome with the lowest quality.
+    //
+    // Star1 pruning: After searching some of the outcomes, the value of the chance node is bounded by the values found so far
+    // plus the bounds of the rest. Each outcome is searched with the window that would move these bounds past the node's
+    // window. If the outcome's value falls outside of its window, the node's value falls outside of the node's window, and the
+    // rest of the outcomes do not need to be searched.
+    let mut expected_value = 0.0;
+    let mut worst_quality = i16::MAX;
+    for (i, (response, probability)) in outcomes.iter().enumerate() {
+        let lower_rest: f32 = outcomes[i + 1..].iter().zip(&lower[i + 1..]).map(|((_, p), v)| p * v).sum();
+        let upper_rest: f32 = outcomes[i + 1..].iter().zip(&upper[i + 1..]).map(|((_, p), v)| p * v).sum();
+        let (outcome_alpha, outcome_beta) = if context.pruning == ChancePruning::Disabled {
+            (-f32::INFINITY, f32::INFINITY)
+        } else {
+            (
+                (alpha - expected_value - upper_rest) / probability,
+                (beta - expected_value - lower_rest) / probability,
+            )
+        };
+
+        let (value, quality) = search_response(context, response, outcome_alpha, outcome_beta, depth + 1);
+        expected_value += probability * value;
+        worst_quality = worst_quality.min(quality);
+
+        // Cutoffs. The value of this node is at most (or at least) the returned bound, which is outside of the window.
+        if value <= outcome_alpha {
+            return Some((expected_value + upper_rest, worst_quality + 1));
+        }
+        if value >= outcome_beta {
+            return Some((expected_value + lower_rest, worst_quality + 1));
+        }
+    }
+
+    context
+        .tt
+        .borrow_mut()
+        .update_with_key(state.fingerprint(), state.verification_key(), expected_value, worst_quality + 1);
+    Some((expected_value, worst_quality + 1))
+}
+
+// Star2 probing phase. Tightens the bounds on the values of the outcomes by searching only the first response to each one. The
+// value of Alice's best response is a lower bound on the value of a state in which Alice moves, and the value of Bob's best
+// response is an upper bound on the value of a state in which Bob moves. Returns a bound on the value of the chance node if
+// the bounds show that it is outside of the window.
+fn probe_outcomes<S, E, R>(
+    context: &Context<S, E, R>,
+    outcomes: &[(Response<S>, f32)],
+    lower: &mut [f32],
+    upper: &mut [f32],
+    alpha: f32,
+    beta: f32,
+    depth: i32,
+) -> Option<f32>
+where
+    S: ChanceState,
+    E: StaticEvaluator<S>,
+    R: ChanceResponseGenerator<State = S>,
+{
+    let weighted_sum = |bounds: &[f32]| -> f32 { outcomes.iter().zip(bounds).map(|((_, p), v)| p * v).sum() };
+
+    // Lower bounds can only cause a cutoff if beta is less than the highest possible value, and upper bounds can only cause a
+    // cutoff if alpha is greater than the lowest possible value.
+    let probe_alice = beta < context.sef.alice_wins_value();
+    let probe_bob = alpha > context.sef.bob_wins_value();
+
+    for (i, (response, probability)) in outcomes.iter().enumerate() {
+        let state = &response.state;
+        if lower[i] == upper[i] || state.is_chance_node() {
+            continue;
+        }
+
+        if state.whose_turn() == PlayerId::ALICE as u8 {
+            if !probe_alice {
+                continue;
+            }
+            let others = weighted_sum(lower) - probability * lower[i];
+            let outcome_beta = (beta - others) / probability;
+            if let Some(bound) = probe(context, state, -f32::INFINITY, outcome_beta, depth + 1) {
+                lower[i] = lower[i].max(bound);
+            }
+            let lower_bound = weighted_sum(lower);
+            if lower_bound >= beta {
+                return Some(lower_bound);
+            }
+        } else {
+            if !probe_bob {
+                continue;
+            }
+            let others = weighted_sum(upper) - probability * upper[i];
+            let outcome_alpha = (alpha - others) / probability;
+            if let Some(bound) = probe(context, state, outcome_alpha, f32::INFINITY, depth + 1) {
+                upper[i] = upper[i].min(bound);
+            }
+            let upper_bound = weighted_sum(upper);
+            if upper_bound <= alpha {
+                return Some(upper_bound);
+            }
+        }
+    }
+    None
+}
+
+// Searches only the most promising response to the given state. The result is a lower bound on the value of the state if it is
+// Alice's turn, or an upper bound if it is Bob's turn. Returns None if there are no responses.
+fn probe<S, E, R>(context: &Context<S, E, R>, state: &Rc<S>, alpha: f32, beta: f32, depth: i32) -> Option<f32>
+where
+    S: ChanceState,
+    E: StaticEvaluator<S>,
+    R: ChanceResponseGenerator<State = S>,
+{
+    let responses = generate_responses(context, context.rg.generate(state, depth));
+    let first = if state.whose_turn() == PlayerId::ALICE as u8 {
+        responses.iter().max_by(|a, b| a.value.total_cmp(&b.value))
+    } else {
+        responses.iter().min_by(|a, b| a.value.total_cmp(&b.value))
+    }?;
+    let (value, _) = search_response(context, first, alpha, beta, depth + 1);
+    Some(value)
+}
+
+// Returns the value and quality of a response at the given depth, replacing its preliminary value with the result of a search
+// if the search is warranted.
+fn search_response<S, E, R>(
+    context: &Context<S, E, R>,
+    response: &Response<S>,
+    alpha: f32,
+    beta: f32,
+    response_depth: i32,
+) -> (f32, i16)
+where
+    S: ChanceState,
+    E: StaticEvaluator<S>,
+    R: ChanceResponseGenerator<State = S>,
+{
+    if !needs_search(context, response, response_depth) {
+        return (response.value, response.quality);
+    }
+
+    let state = &response.state;
+    let result = if state.is_chance_node() {
+        chance_search(context, state, alpha, beta, response_depth)
+    } else if state.whose_turn() == PlayerId::ALICE as u8 {
+        alice_search(context, state, alpha, beta, response_depth).map(|r| (r.value, r.quality))
+    } else {
+        bob_search(context, state, alpha, beta, response_depth).map(|r| (r.value, r.quality))
+    };
+
+    // If there is no response or outcome, then leave the value and quality as is.
+    result.unwrap_or((response.value, response.quality))
+}
+
+// Returns true if the preliminary value of a response at the given depth should be replaced by the result of a search. The
+// search is skipped if the preliminary value is decisive, if its quality is already at least as good as the quality of a
+// search, or if the maximum depth has been reached.
+fn needs_search<S, E, R>(context: &Context<S, E, R>, response: &Response<S>, response_depth: i32) -> bool
+where
+    S: ChanceState,
+    E: StaticEvaluator<S>,
+    R: ChanceResponseGenerator<State = S>,
+{
+    // Quality of a value as a result of a search of the response
+    let search_quality = (context.max_depth - response_depth) as i16;
+
+    response.value < context.sef.alice_wins_value()
+        && response.value > context.sef.bob_wins_value()
+        && response_depth < context.max_depth
+        && response.quality < search_quality
+}
+
+// Returns true if a value returned by a search with the given window is exact rather than a bound
+fn is_exact(value: f32, alpha: f32, beta: f32) -> bool {
+    alpha < value && value < beta
+}
+
+// Generates a list of responses with preliminary values from a list of states
+fn generate_responses<S, E, R>(context: &Context<S, E, R>, states: Vec<Box<S>>) -> Vec<Response<S>>
+where
+    S: ChanceState,
+    E: StaticEvaluator<S>,
+    R: ChanceResponseGenerator<State = S>,
+{
+    states
+        .into_iter()
+        .map(|state| preliminary_response(context, state))
+        .collect()
+}
+
+// Creates a response with a preliminary value of the state from the static evaluator or the transposition table
+fn preliminary_response<S, E, R>(context: &Context<S, E, R>, state: Box<S>) -> Response<S>
+where
+    S: ChanceState,
+    E: StaticEvaluator<S>,
+    R: ChanceResponseGenerator<State = S>,
+{
+    let state: Rc<S> = Rc::from(state);
+
+    // A drawn state is not evaluated
+    if state.is_terminal() && state.is_draw() {
+        return Response {
+            state,
+            value: context.sef.draw_value(),
+            quality: SEF_QUALITY,
+        };
+    }
+
+    // If it is in the T-table then use that value, otherwise evaluate the state and save the value.
+    let fingerprint = state.fingerprint();
+    let key = state.verification_key();
+    if let Some((value, quality)) = context.tt.borrow_mut().check_with_key(fingerprint, key, -1) {
+        return Response { state, value, quality };
+    }
+
+    let value = context.sef.evaluate(&state);
+    context.tt.borrow_mut().update_with_key(fingerprint, key, value, SEF_QUALITY);
+    Response {
+        state,
+        value,
+        quality: SEF_QUALITY,
+    }
+}
@@ -6,6 +6,10 @@
 //! # Minimax Search
 //!
 //! The crate includes a minimax search implementation with alpha-beta pruning and a transposition table to optimize performance.
+//! For games with chance events (e.g. dice), the [`expectiminimax`] module provides a search over chance nodes. For games with
+//! more than two players, the [`multiplayer`] module provides max^n and paranoid searches. To prove whether a player can force
+//! a win rather than estimate the value of a state, the [`pns`] module provides proof-number searches, and the [`retrograde`]
+//! module generates endgame tablebases. The [`book`] module provides an opening book that is consulted before searching.
 //!
 //! ## Key Integration Points
 //!
@@ -129,11 +133,18 @@
 //! }
 //! ```
 
+pub mod book;
+pub mod expectiminimax;
 pub mod minimax;
+pub mod multiplayer;
+pub mod pns;
+pub mod retrograde;
+pub mod score;
 pub mod state;
 pub mod static_evaluator;
 pub mod transposition_table;
 
+pub use score::Score;
 pub use state::{PlayerId, State};
 pub use static_evaluator::StaticEvaluator;
 pub use transposition_table::TranspositionTable;
@@ -22,6 +22,10 @@
 //! }
 //! ```
 //!
+//! # Make/Unmake
+//! The search creates a new state for every response it considers. For games in which states are expensive to create, the
+//! [`in_place`] module provides a search that applies and undoes actions on a single state instead.
+//!
 //! # Notes
 //! - The search assumes a two-player zero-sum game with perfect information.
 //! - The transposition table can be reused across multiple searches for efficiency and support of iterative deepening.
@@ -29,33 +33,212 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use crate::pns::GameResult;
+use crate::score::Score;
 use crate::state::*;
 use crate::static_evaluator::*;
 use crate::transposition_table::*;
 
+pub mod in_place;
+pub mod probcut;
+
+use probcut::ProbCut;
+
 static SEF_QUALITY: i16 = 0; // Quality of a value returned by the static evaluation function
 
+/// The maximum number of plies to the end of the game reflected in the value of a win that is known by an oracle.
+///
+/// See the [`Oracles`](search_with_config#oracles) section of [`search_with_config`].
+pub const MAX_WIN_PLIES: i32 = 64;
+
 // Holds evaluation information about a response.
-struct Response<S> {
+struct Response<S, V> {
     // Reference to the resulting state
     state: Rc<S>,
     // Value of the state
-    value: f32,
+    value: V,
     // Quality of the value. Quality is the number of plies searched to find the value.
     quality: i16,
+    // True if the value depends on the path to the state (i.e. a repetition was found) or on the player to move at the root
+    // (i.e. a draw was found and there is contempt), and must not be saved in the T-table
+    path_dependent: bool,
 }
 
 // Holds static information pertaining to the search.
-struct Context<'a, S, E: StaticEvaluator<S>, R: ResponseGenerator<State = S>>
+struct Context<'a, S, V, E: StaticEvaluator<S, V>, R: ResponseGenerator<State = S>>
 where
     S: State,
+    V: Score,
 {
-    max_depth: i32,
     rg: &'a R,
     sef: &'a E,
-    tt: &'a Rc<RefCell<TranspositionTable>>,
+    tt: &'a Rc<RefCell<TranspositionTable<V>>>,
+    // Value of a draw, adjusted by the contempt for the player to move at the root
+    draw_value: V,
+    // True if the value of a draw depends on the player to move at the root (i.e. there is contempt)
+    contempt: bool,
+    // True if the value of a win known by an oracle reflects the number of plies to the end of the game
+    win_distance: bool,
+    // Fingerprints of the game history and of the states from the root to the state being searched
+    path: RefCell<Vec<u64>>,
+    // Internal iterative deepening options
+    iid: Option<InternalIterativeDeepening>,
+    // Singular extension options
+    singular: Option<SingularExtensions<V>>,
+    // Responses are not extended beyond this depth
+    max_extended_depth: i32,
+    // ProbCut options
+    probcut: Option<ProbCut>,
+    statistics: RefCell<Statistics>,
     _phantom: std::marker::PhantomData<S>,
 }
+
+// Records a state on the search path for as long as it is in scope.
+struct Ancestor<'a> {
+    path: &'a RefCell<Vec<u64>>,
+}
+
+impl<'a> Ancestor<'a> {
+    fn push(path: &'a RefCell<Vec<u64>>, fingerprint: u64) -> Self {
+        path.borrow_mut().push(fingerprint);
+        Self { path }
+    }
+}
+
+impl Drop for Ancestor<'_> {
+    fn drop(&mut self) {
+        self.path.borrow_mut().pop();
+    }
+}
+
+/// Search options.
+///
+/// The type of the values is `V`, which is `f32` by default and must match the values of the static evaluator. The default
+/// configuration has no contempt and no game history, and internal iterative deepening, singular extensions, and ProbCut are
+/// disabled.
+///
+/// # Examples
+/// ```rust
+/// # use game_player::minimax::Config;
+/// // The player to move at the root treats a draw as 25 worse than the evaluator's draw value
+/// let config = Config::<i16> { contempt: 25, ..Config::default() };
+/// assert_eq!(config.contempt, 25);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config<V = f32> {
+    /// The contempt factor. The amount by which the player to move at the root considers a draw to be worse than the value
+    /// returned by [`StaticEvaluator::draw_value`]. A positive value avoids draws (e.g. against a weaker opponent) and a
+    /// negative value seeks them.
+    pub contempt: V,
+    /// Fingerprints of the states preceding the state being searched, in the order they occurred. A state in the search that
+    /// repeats one of these states or one of its own ancestors is scored as a repetition.
+    pub history: Vec<u64>,
+    /// Internal iterative deepening options, or `None` to disable internal iterative deepening
+    pub iid: Option<InternalIterativeDeepening>,
+    /// Singular extension options, or `None` to disable singular extensions
+    pub singular: Option<SingularExtensions<V>>,
+    /// ProbCut options, or `None` to disable ProbCut
+    pub probcut: Option<ProbCut>,
+}
+
+impl<V: Score> Default for Config<V> {
+    fn default() -> Self {
+        Self {
+            contempt: V::default(),
+            history: Vec::new(),
+            iid: None,
+            singular: None,
+            probcut: None,
+        }
+    }
+}
+
+/// Internal iterative deepening options.
+///
+/// The responses to a state are searched in the order of their preliminary values. If a state has not been searched before,
+/// then these are mostly the values returned by the static evaluator, which are not very good at predicting the best
+/// response. Internal iterative deepening first searches such a state to a reduced depth. The values found by that search are
+/// saved in the transposition table and become the preliminary values of the full search, which results in better ordering
+/// and more pruning. This is worthwhile only for states with a lot of remaining depth.
+///
+/// # Examples
+/// ```rust
+/// # use game_player::minimax::{Config, InternalIterativeDeepening};
+/// let config: Config = Config {
+///     iid: Some(InternalIterativeDeepening { min_depth: 5, reduction: 3 }),
+///     ..Config::default()
+/// };
+/// assert_eq!(InternalIterativeDeepening::default().reduction, 2);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InternalIterativeDeepening {
+    /// The minimum remaining depth, in plies, of a state to be searched in advance. The default is 4.
+    pub min_depth: i32,
+    /// The number of plies by which the search in advance is reduced. The default is 2.
+    pub reduction: i32,
+}
+
+impl Default for InternalIterativeDeepening {
+    fn default() -> Self {
+        Self {
+            min_depth: 4,
+            reduction: 2,
+        }
+    }
+}
+
+/// Singular extension options.
+///
+/// A response is singular if it is much better than all of the alternatives, for example because it is the only response that
+/// does not lose. A fixed-depth search can misjudge such a response, because the consequences of having no alternative can lie
+/// just beyond the search depth. A singular response is searched one ply deeper than the other responses.
+///
+/// The candidate is the response with the best preliminary value, if that value is the result of a previous search (i.e. it
+/// was found in the transposition table). It is singular if a reduced-depth search of each of the alternatives shows that
+/// none of them is within `margin` of the candidate's value. Only the responses in the first stage (see
+/// [`ResponseGenerator::generate_staged`]) are considered. A state with only one response is singular.
+///
+/// # Examples
+/// ```rust
+/// # use game_player::minimax::{Config, SingularExtensions};
+/// // Extend a response if the alternatives are worse by more than 0.5 at a depth reduced by 3 plies
+/// let config: Config = Config {
+///     singular: Some(SingularExtensions { margin: 0.5, min_depth: 6, reduction: 3, max_extensions: 4 }),
+///     ..Config::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SingularExtensions<V = f32> {
+    /// The amount by which the candidate must be better than every alternative in order to be singular. Since it depends on
+    /// the range of values returned by the static evaluator, there is no default.
+    pub margin: V,
+    /// The minimum remaining depth, in plies, of a state for its responses to be checked.
+    pub min_depth: i32,
+    /// The number of plies by which the searches of the alternatives are reduced.
+    pub reduction: i32,
+    /// The maximum number of plies by which the search can be extended beyond `max_depth`.
+    pub max_extensions: i32,
+}
+
+/// Search statistics.
+///
+/// The statistics are accumulated by [`search_with_statistics`], so the same instance can be used for several searches.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Statistics {
+    /// The number of states whose responses were searched
+    pub nodes: u64,
+    /// The number of states evaluated by the static evaluator
+    pub evaluations: u64,
+    /// The number of internal iterative deepening searches
+    pub iid_searches: u64,
+    /// The number of responses extended because they were singular
+    pub singular_extensions: u64,
+    /// The number of states that were not searched because ProbCut predicted a cutoff
+    pub probcut_cutoffs: u64,
+    /// The number of states whose results were given by the static evaluator's oracle
+    pub oracle_hits: u64,
+}
+
 /// Response generator function object trait.
 ///
 /// This trait defines the interface for generating all possible responses from a given state. Implementers should provide
@@ -123,6 +306,45 @@ pub trait ResponseGenerator {
     /// Returning no responses indicates that the player cannot respond. It does not necessarily indicate that the game is
     /// over or that the player has passed. If passing is allowed, then a "pass" state should be a valid response.
     fn generate(&self, state: &Rc<Self::State>, depth: i32) -> Vec<Box<Self::State>>;
+
+    /// Generates the possible responses to the given state in stages.
+    ///
+    /// Each item is a stage, which is a batch of responses. The search considers the stages in order, and stops taking stages
+    /// from the iterator once it has found a cutoff or a win. A game can use this to avoid generating responses that are
+    /// unlikely to be searched, for example by returning a stage with the best response from a previous search, then a stage
+    /// of captures, and then a stage of the remaining moves. Within a stage, the responses are ordered by their preliminary
+    /// values.
+    ///
+    /// The default implementation returns all of the responses from [`generate`](Self::generate) as a single stage.
+    ///
+    /// # Arguments
+    /// * `state` - The current state to generate responses for
+    /// * `depth` - Current search depth (ply number), useful for optimizations
+    ///
+    /// # Returns
+    /// An iterator over the stages. Together, the stages must contain each possible response exactly once. Empty stages are
+    /// allowed.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// impl ResponseGenerator for MyResponseGenerator {
+    ///     type State = MyGameState;
+    ///
+    ///     fn generate(&self, state: &Rc<Self::State>, depth: i32) -> Vec<Box<Self::State>> {
+    ///         self.generate_staged(state, depth).flatten().collect()
+    ///     }
+    ///
+    ///     fn generate_staged(&self, state: &Rc<Self::State>, _depth: i32) -> impl Iterator<Item = Vec<Box<Self::State>>> {
+    ///         // Captures first. The quiet moves are only generated if the captures do not cause a cutoff.
+    ///         let captures = std::iter::once_with(|| captures(state));
+    ///         let quiet_moves = std::iter::once_with(|| quiet_moves(state));
+    ///         captures.chain(quiet_moves)
+    ///     }
+    /// }
+    /// ```
+    fn generate_staged(&self, state: &Rc<Self::State>, depth: i32) -> impl Iterator<Item = Vec<Box<Self::State>>> {
+        std::iter::once(self.generate(state, depth))
+    }
 }
 
 /// A minimax search implementation using alpha-beta pruning and a transposition table.
@@ -132,7 +354,8 @@ pub trait ResponseGenerator {
 ///
 /// # Type Parameters
 /// * `S` - Game state type that implements the `State<A>` trait
-/// * `E` - Static evaluator type that implements `StaticEvaluator<S>`
+/// * `V` - Value type that implements the `Score` trait (`f32` by default)
+/// * `E` - Static evaluator type that implements `StaticEvaluator<S, V>`
 /// * `A` - Action/move type used by the game
 /// * `R` - Response generator type that implements `ResponseGenerator<S>`
 ///
@@ -174,313 +397,736 @@ pub trait ResponseGenerator {
 /// - **Alpha-beta pruning**: Early termination of unpromising branches
 /// - **Transposition table**: Caching of previously evaluated positions
 /// - **Move ordering**: Better moves searched first for more effective pruning
-pub fn search<S, E, R>(
-    tt: &Rc<RefCell<TranspositionTable>>,
+pub fn search<S, V, E, R>(
+    tt: &Rc<RefCell<TranspositionTable<V>>>,
+    sef: &E,
+    rg: &R,
+    s0: &Rc<S>,
+    max_depth: i32,
+) -> Option<Rc<S>>
+where
+    S: State,
+    V: Score,
+    E: StaticEvaluator<S, V>,
+    R: ResponseGenerator<State = S>,
+{
+    search_with_config(tt, sef, rg, s0, max_depth, &Config::default())
+}
+
+/// A minimax search with the given options.
+///
+/// This is the same as [`search`], except that the search is configured by `config`.
+///
+/// # Arguments
+/// * `tt` - A transposition table for caching previously computed positions. Can be reused across multiple searches.
+/// * `sef` - The static evaluation function
+/// * `rg` - The response generator
+/// * `s0` - The state to search from
+/// * `max_depth` - Maximum search depth in plies
+/// * `config` - Search options
+///
+/// # Returns
+/// `Some(Rc<S>)` containing the best move found, or `None` if no valid moves exist
+///
+/// # Draws
+/// A terminal state that reports a draw (see [`State::is_draw`]) is given the value [`StaticEvaluator::draw_value`] adjusted
+/// by `config.contempt` from the perspective of the player to move in `s0`. Since the adjusted value depends on the root
+/// player, if there is contempt, then neither the value of a draw nor the value of a state whose search encountered a draw is
+/// stored in the transposition table.
+///
+/// # Repetitions
+/// A state that repeats a state in `config.history` or one of its ancestors in the search is not searched further. It is
+/// given the value returned by [`StaticEvaluator::repetition_value`], or the draw value (as above) if that returns `None`.
+/// The value of a state whose search encountered a repetition depends on the path to it, so it is not stored in the
+/// transposition table.
+///
+/// # Oracles
+/// If the static evaluator has an oracle (see [`StaticEvaluator::oracle`]), a state whose result the oracle knows is not
+/// evaluated or searched further. A win for Alice is given [`StaticEvaluator::alice_wins_value`] less the number of plies
+/// from `s0` to the end of the game, and a win for Bob is given [`StaticEvaluator::bob_wins_value`] plus that number, so
+/// that the quickest win and the slowest loss are chosen. A draw is given the draw value. The number of plies is limited to
+/// [`MAX_WIN_PLIES`]` - 1`, so a value within [`MAX_WIN_PLIES`] of a win value is assumed to be such a win, and the values of
+/// other states should not be that close. In the transposition table, the value of such a win is stored relative to the
+/// state rather than `s0`, so the table can be reused for searches from other states.
+///
+/// The number of plies is reflected only if both win values are more than [`MAX_WIN_PLIES`] from
+/// [`StaticEvaluator::draw_value`]. Otherwise, a win is given the win value unchanged.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use game_player::minimax::{search_with_config, Config};
+///
+/// // Against a weaker opponent, prefer a slightly worse position over a draw
+/// let config = Config { contempt: 0.5, ..Config::default() };
+/// let best_move = search_with_config(&transposition_table, &evaluator, &move_generator, &game_state, 6, &config);
+/// ```
+pub fn search_with_config<S, V, E, R>(
+    tt: &Rc<RefCell<TranspositionTable<V>>>,
+    sef: &E,
+    rg: &R,
+    s0: &Rc<S>,
+    max_depth: i32,
+    config: &Config<V>,
+) -> Option<Rc<S>>
+where
+    S: State,
+    V: Score,
+    E: StaticEvaluator<S, V>,
+    R: ResponseGenerator<State = S>,
+{
+    search_with_statistics(tt, sef, rg, s0, max_depth, config, &mut Statistics::default())
+}
+
+/// A minimax search with the given options, which reports statistics.
+///
+/// This is the same as [`search_with_config`], except that the statistics of the search are added to `statistics`.
+///
+/// # Arguments
+/// * `tt` - A transposition table for caching previously computed positions. Can be reused across multiple searches.
+/// * `sef` - The static evaluation function
+/// * `rg` - The response generator
+/// * `s0` - The state to search from
+/// * `max_depth` - Maximum search depth in plies
+/// * `config` - Search options
+/// * `statistics` - Statistics to which the statistics of this search are added
+///
+/// # Returns
+/// `Some(Rc<S>)` containing the best move found, or `None` if no valid moves exist
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use game_player::minimax::{search_with_statistics, Config, Statistics};
+///
+/// let mut statistics = Statistics::default();
+/// let config = Config::default();
+/// let best_move = search_with_statistics(&tt, &evaluator, &move_generator, &game_state, 6, &config, &mut statistics);
+/// println!("{} nodes searched", statistics.nodes);
+/// ```
+pub fn search_with_statistics<S, V, E, R>(
+    tt: &Rc<RefCell<TranspositionTable<V>>>,
     sef: &E,
     rg: &R,
     s0: &Rc<S>,
     max_depth: i32,
+    config: &Config<V>,
+    statistics: &mut Statistics,
 ) -> Option<Rc<S>>
 where
     S: State,
-    E: StaticEvaluator<S>,
+    V: Score,
+    E: StaticEvaluator<S, V>,
+    R: ResponseGenerator<State = S>,
+{
+    search_response(tt, sef, rg, s0, max_depth, config, statistics).map(|response| response.state)
+}
+
+// Searches the state and returns the best response to it along with its value
+fn search_response<S, V, E, R>(
+    tt: &Rc<RefCell<TranspositionTable<V>>>,
+    sef: &E,
+    rg: &R,
+    s0: &Rc<S>,
+    max_depth: i32,
+    config: &Config<V>,
+    statistics: &mut Statistics,
+) -> Option<Response<S, V>>
+where
+    S: State,
+    V: Score,
+    E: StaticEvaluator<S, V>,
     R: ResponseGenerator<State = S>,
 {
+    let draw_value = contempt_draw_value(sef, s0.whose_turn(), config.contempt);
+
     let context = Context {
         tt,
         sef,
         rg,
-        max_depth,
+        draw_value,
+        contempt: config.contempt != V::default(),
+        win_distance: scores_win_distance(sef),
+        path: RefCell::new(config.history.clone()),
+        iid: config.iid,
+        singular: config.singular,
+        max_extended_depth: max_depth + config.singular.map_or(0, |singular| singular.max_extensions),
+        probcut: config.probcut,
+        statistics: RefCell::new(std::mem::take(statistics)),
         _phantom: std::marker::PhantomData,
     };
-    if s0.whose_turn() == PlayerId::ALICE as u8 {
-        if let Some(response) = alice_search(&context, s0, -f32::INFINITY, f32::INFINITY, 0) {
-            return Some(Rc::clone(&response.state));
-        }
+    let response = if s0.whose_turn() == PlayerId::ALICE as u8 {
+        alice_search(&context, s0, V::MIN, V::MAX, 0, max_depth)
     } else {
-        if let Some(response) = bob_search(&context, s0, -f32::INFINITY, f32::INFINITY, 0) {
-            return Some(Rc::clone(&response.state));
-        }
+        bob_search(&context, s0, V::MIN, V::MAX, 0, max_depth)
+    };
+    *statistics = context.statistics.into_inner();
+    response
+}
+
+// Returns the value of a draw adjusted by the contempt of the player to move at the root
+fn contempt_draw_value<S, V, E>(sef: &E, root_player: u8, contempt: V) -> V
+where
+    V: Score,
+    E: StaticEvaluator<S, V>,
+{
+    // Contempt makes a draw less attractive to the player to move at the root. Alice maximizes, so a draw is worth less to
+    // her. Bob minimizes, so a draw is worth more to him.
+    if root_player == PlayerId::ALICE as u8 {
+        sef.draw_value().saturating_sub(contempt)
+    } else {
+        sef.draw_value().saturating_add(contempt)
     }
-    None
 }
 
 // Evaluates all of Alice's possible responses to the given state. The returned response is the one with the highest value.
-fn alice_search<S, E, R>(
-    context: &Context<S, E, R>,
+fn alice_search<S, V, E, R>(
+    context: &Context<S, V, E, R>,
     state: &Rc<S>,
-    mut alpha: f32,
-    beta: f32,
+    mut alpha: V,
+    beta: V,
     depth: i32,
-) -> Option<Response<S>>
+    max_depth: i32,
+) -> Option<Response<S, V>>
 where
     S: State,
-    E: StaticEvaluator<S>,
+    V: Score,
+    E: StaticEvaluator<S, V>,
     R: ResponseGenerator<State = S>,
 {
     // Depth of responses to this state
     let response_depth = depth + 1;
     // Quality of a response as a result of a search at this depth.
-    let search_quality = (context.max_depth - response_depth) as i16;
+    let search_quality = (max_depth - response_depth) as i16;
+    // A search with a null window (e.g. a ProbCut probe) only determines whether the value is above or below the window, so its
+    // value is a bound.
+    let null_window = alpha.total_cmp(&beta).is_eq();
 
-    // Generate a list of the possible responses to this state by Alice. The responses are initialized with preliminary values.
-    let mut responses = generate_responses(context, state, depth);
+    // If a shallow search predicts that the result of the search would be outside of the window, then don't search.
+    if let Some(response) = probcut::cut(context, state, PlayerId::ALICE, alpha, beta, depth, max_depth) {
+        return Some(response);
+    }
 
-    // If there are no responses, return without a response. It's up to the caller to decide how to handle this case.
-    if responses.is_empty() {
-        return None;
+    // If this state has not been searched before, search it to a reduced depth first in order to improve the preliminary
+    // values of the responses.
+    if let Some(iid_max_depth) = iid_max_depth(context, state, depth, max_depth) {
+        context.statistics.borrow_mut().iid_searches += 1;
+        alice_search(context, state, alpha, beta, depth, iid_max_depth);
     }
 
-    // Sort from highest to lowest in order to increase the chance of triggering a beta cutoff earlier.
-    responses.sort_by(|a, b| {
-        b.value.partial_cmp(&a.value).unwrap_or(std::cmp::Ordering::Equal)
-    });
+    // This state is an ancestor of all states searched from here until this function returns.
+    let _ancestor = Ancestor::push(&context.path, state.fingerprint());
+    context.statistics.borrow_mut().nodes += 1;
 
     // Evaluate each of the responses and choose the one with the highest value
-    let mut best_state: Option<&Rc<S>> = None;
-    let mut best_value = -f32::INFINITY;
+    let mut best_state: Option<Rc<S>> = None;
+    let mut best_value = V::MIN;
     let mut best_quality = -1;
     let mut pruned = false;
+    let mut path_dependent = false;
+
+    // Responses are generated in stages (see ResponseGenerator::generate_staged). Once the search of this state is finished
+    // (e.g. because of a cutoff), no more stages are generated.
+    'stages: for (stage_index, stage) in context.rg.generate_staged(state, depth).enumerate() {
+        // The responses in this stage are initialized with preliminary values.
+        let mut responses = generate_responses(context, stage, depth, max_depth);
+
+        // Sort from highest to lowest in order to increase the chance of triggering a beta cutoff earlier.
+        responses.sort_by(|a, b| b.value.total_cmp(&a.value));
+
+        // If the first response is singular, then it is searched one ply deeper.
+        let extend_first = stage_index == 0 && is_singular(context, &responses, PlayerId::ALICE, depth, max_depth);
 
-    for response in &responses {
-        // Replace the preliminary value and quality of this response with the value and quality of Bob's subsequent response
-        // to it.
-        // The following conditions will cause the search to be skipped:
-        // 1. The preliminary value indicates a win for Alice.
-        // 2. The preliminary quality is more than the quality of a search. This can be a result of obtaining the preliminary
-        //    value from the result of a previous search stored in the transposition table.
-        // 3. The search has reached its maximum depth.
-        let mut value = response.value;
-        let mut quality = response.quality;
-        if value < context.sef.alice_wins_value()
-            && response_depth < context.max_depth
-            && quality < search_quality
-        {
-            // Update the value of Alice's response by evaluating Bob's responses to it. If Bob has no response, then
-            // leave the response's value and quality as is.
-            if let Some(bob_response) =
-                bob_search(&context, &response.state, alpha, beta, response_depth)
+        for (i, response) in responses.into_iter().enumerate() {
+            let extension = i32::from(extend_first && i == 0);
+            // Replace the preliminary value and quality of this response with the value and quality of Bob's subsequent
+            // response to it. The following conditions will cause the search to be skipped:
+            // 1. The preliminary value indicates a win for Alice.
+            // 2. The preliminary quality is more than the quality of a search. This can be a result of obtaining the
+            //    preliminary value from the result of a previous search stored in the transposition table.
+            // 3. The search has reached its maximum depth.
+            // 4. The response repeats a previous state.
+            let mut value = response.value;
+            let mut quality = response.quality;
+            let mut response_path_dependent = response.path_dependent;
+            if value.total_cmp(&context.sef.alice_wins_value()).is_lt()
+                && response_depth < max_depth + extension
+                && quality < search_quality + extension as i16
+                && !response.path_dependent
             {
-                value = bob_response.value;
-                quality = bob_response.quality;
+                // Update the value of Alice's response by evaluating Bob's responses to it. If Bob has no response, then
+                // leave the response's value and quality as is.
+                if let Some(bob_response) =
+                    bob_search(context, &response.state, alpha, beta, response_depth, max_depth + extension)
+                {
+                    value = bob_response.value;
+                    quality = bob_response.quality;
+                    response_path_dependent = bob_response.path_dependent;
+                }
             }
-        }
 
-        // Determine if this response's value is the best so far. If so, then save the value and do alpha-beta pruning
-        if value > best_value {
-            // Save it
-            best_state = Some(&response.state);
-            best_value = value;
-            best_quality = quality;
+            // If the value of any response depends on the path, then so does the value of this state
+            path_dependent |= response_path_dependent;
 
-            // If Alice wins with this response, then there is no reason to look for anything better
-            if best_value >= context.sef.alice_wins_value() {
-                break;
-            }
+            // Determine if this response's value is the best so far. If so, then save the value and do alpha-beta pruning
+            if best_state.is_none() || value.total_cmp(&best_value).is_gt() {
+                // Save it
+                best_state = Some(response.state);
+                best_value = value;
+                best_quality = quality;
 
-            // alpha-beta pruning (beta cutoff) Here's how it works:
-            //
-            // Bob is looking for the lowest value. The 'beta' is the value of Bob's best response found so far in the previous
-            // ply. If the value of this response is higher than the beta, then Bob will never choose a response leading to this
-            // response because the result is worse than the result of a response Bob has already found. As such, there is no
-            // reason to continue.
-            if best_value > beta {
-                // Beta cutoff
-                pruned = true;
-                break;
-            }
+                // If Alice wins with this response, then there is no reason to look for anything better
+                if best_value.total_cmp(&context.sef.alice_wins_value()).is_ge() {
+                    break 'stages;
+                }
+
+                // alpha-beta pruning (beta cutoff) Here's how it works:
+                //
+                // Bob is looking for the lowest value. The 'beta' is the value of Bob's best response found so far in the
+                // previous ply. If the value of this response is higher than the beta, then Bob will never choose a response
+                // leading to this response because the result is worse than the result of a response Bob has already found. As
+                // such, there is no reason to continue.
+                if best_value.total_cmp(&beta).is_gt() {
+                    // Beta cutoff
+                    pruned = true;
+                    break 'stages;
+                }
 
-            // alpha-beta pruning (alpha) Here's how it works:
-            //
-            // Alice is looking for the highest value. The 'alpha' is the value of Alice's best response found so far. If the
-            // value of this response is higher than the alpha, then it is a better response for Alice. The alpha is
-            // subsequently passed to Bob's search so that if Bob finds a response with a lower value than the alpha, then
-            // there is no reason to continue because Alice already has a better response and will choose it instead of allowing
-            // Bob to make a move with a lower value.
-            if best_value > alpha {
-                alpha = best_value;
+                // alpha-beta pruning (alpha) Here's how it works:
+                //
+                // Alice is looking for the highest value. The 'alpha' is the value of Alice's best response found so far. If
+                // the value of this response is higher than the alpha, then it is a better response for Alice. The alpha is
+                // subsequently passed to Bob's search so that if Bob finds a response with a lower value than the alpha, then
+                // there is no reason to continue because Alice already has a better response and will choose it instead of
+                // allowing Bob to make a move with a lower value.
+                if best_value.total_cmp(&alpha).is_gt() {
+                    alpha = best_value;
+                }
             }
         }
     }
 
-    assert!(best_value > -f32::INFINITY); // Sanity check
+    // If there are no responses, return without a response. It's up to the caller to decide how to handle this case.
+    let best_state = best_state?;
     assert!(best_quality >= 0); // Sanity check
-    assert!(best_state.is_some()); // Sanity check
-
-    // Just in case
-    best_state.as_ref()?;
 
     // At this point, the value of this state becomes the value of the best response to it, and the quality becomes its
     // quality + 1.
     //
     // Save the value of this state in the T-table if the ply was not pruned. Pruning results in an incorrect value because the
-    // search was interrupted and potentially better responses were not considered.
-    if !pruned {
-        context.tt.borrow_mut().update(state.fingerprint(), best_value, best_quality + 1);
+    // search was interrupted and potentially better responses were not considered. A value that depends on the path to this
+    // state is not saved either, because it is not necessarily the value of the state when reached by a different path. Nor is
+    // the value found by a search with a null window, which is only a bound.
+    if !pruned && !path_dependent && !null_window {
+        context
+            .tt
+            .borrow_mut()
+            .update_with_key(
+                state.fingerprint(),
+                state.verification_key(),
+                shift_win_value(context.sef, context.win_distance, best_value, -depth),
+                best_quality + 1,
+            );
     }
 
-    Some(Response::<S> {
-        state: Rc::clone(best_state?),
+    Some(Response::<S, V> {
+        state: best_state,
         value: best_value,
         quality: best_quality + 1,
+        path_dependent,
     })
 }
 
 // Evaluates all of Bob's possible responses to the given state. The returned response is the one with the lowest value.
-fn bob_search<S, E, R>(
-    context: &Context<S, E, R>,
+fn bob_search<S, V, E, R>(
+    context: &Context<S, V, E, R>,
     state: &Rc<S>,
-    alpha: f32,
-    mut beta: f32,
+    alpha: V,
+    mut beta: V,
     depth: i32,
-) -> Option<Response<S>>
+    max_depth: i32,
+) -> Option<Response<S, V>>
 where
     S: State,
-    E: StaticEvaluator<S>,
+    V: Score,
+    E: StaticEvaluator<S, V>,
     R: ResponseGenerator<State = S>,
 {
     // Depth of responses to this state
     let response_depth = depth + 1;
     // Quality of a response as a result of a search at this depth.
-    let search_quality = (context.max_depth - response_depth) as i16;
+    let search_quality = (max_depth - response_depth) as i16;
+    // A search with a null window (e.g. a ProbCut probe) only determines whether the value is above or below the window, so its
+    // value is a bound.
+    let null_window = alpha.total_cmp(&beta).is_eq();
 
-    // Generate a list of the possible responses to this state by Bob. The responses are initialized with preliminary values.
-    let mut responses = generate_responses(context, state, depth);
+    // If a shallow search predicts that the result of the search would be outside of the window, then don't search.
+    if let Some(response) = probcut::cut(context, state, PlayerId::BOB, alpha, beta, depth, max_depth) {
+        return Some(response);
+    }
 
-    // If there are no responses, return without a response. It's up to the caller to decide how to handle this case.
-    if responses.is_empty() {
-        return None;
+    // If this state has not been searched before, search it to a reduced depth first in order to improve the preliminary
+    // values of the responses.
+    if let Some(iid_max_depth) = iid_max_depth(context, state, depth, max_depth) {
+        context.statistics.borrow_mut().iid_searches += 1;
+        bob_search(context, state, alpha, beta, depth, iid_max_depth);
     }
 
-    // Sort from lowest to highest in order to increase the chance of triggering an alpha cutoff earlier
-    responses.sort_by(|a, b| {
-        a.value.partial_cmp(&b.value).unwrap_or(std::cmp::Ordering::Equal)
-    });
+    // This state is an ancestor of all states searched from here until this function returns.
+    let _ancestor = Ancestor::push(&context.path, state.fingerprint());
+    context.statistics.borrow_mut().nodes += 1;
 
     // Evaluate each of the responses and choose the one with the lowest value
-    let mut best_state: Option<&Rc<S>> = None;
-    let mut best_value = f32::INFINITY;
+    let mut best_state: Option<Rc<S>> = None;
+    let mut best_value = V::MAX;
     let mut best_quality = -1;
     let mut pruned = false;
+    let mut path_dependent = false;
+
+    // Responses are generated in stages (see ResponseGenerator::generate_staged). Once the search of this state is finished
+    // (e.g. because of a cutoff), no more stages are generated.
+    'stages: for (stage_index, stage) in context.rg.generate_staged(state, depth).enumerate() {
+        // The responses in this stage are initialized with preliminary values.
+        let mut responses = generate_responses(context, stage, depth, max_depth);
 
-    for response in &responses {
-        // Replace the preliminary value and quality of this response with the value and quality of Alice's subsequent response
-        // to it.
-        // The following conditions will cause the search to be skipped:
-        // 1. The preliminary value indicates a win for Bob.
-        // 2. The preliminary quality is more than the quality of a search. This can be a result of obtaining the preliminary
-        //    value from the result of a previous search stored in the transposition table.
-        // 3. The search has reached its maximum depth.
-        let mut value = response.value;
-        let mut quality = response.quality;
-        if value > context.sef.bob_wins_value() && response_depth < context.max_depth && quality < search_quality
-        {
-            // Update the value of Bob's response by evaluating Alice's responses to it. If Alice has no response, then
-            // leave the response's value and quality as is.
-            if let Some(alice_response) =
-                alice_search(context, &response.state, alpha, beta, response_depth)
+        // Sort from lowest to highest in order to increase the chance of triggering an alpha cutoff earlier
+        responses.sort_by(|a, b| a.value.total_cmp(&b.value));
+
+        // If the first response is singular, then it is searched one ply deeper.
+        let extend_first = stage_index == 0 && is_singular(context, &responses, PlayerId::BOB, depth, max_depth);
+
+        for (i, response) in responses.into_iter().enumerate() {
+            let extension = i32::from(extend_first && i == 0);
+            // Replace the preliminary value and quality of this response with the value and quality of Alice's subsequent
+            // response to it. The following conditions will cause the search to be skipped:
+            // 1. The preliminary value indicates a win for Bob.
+            // 2. The preliminary quality is more than the quality of a search. This can be a result of obtaining the
+            //    preliminary value from the result of a previous search stored in the transposition table.
+            // 3. The search has reached its maximum depth.
+            // 4. The response repeats a previous state.
+            let mut value = response.value;
+            let mut quality = response.quality;
+            let mut response_path_dependent = response.path_dependent;
+            if value.total_cmp(&context.sef.bob_wins_value()).is_gt()
+                && response_depth < max_depth + extension
+                && quality < search_quality + extension as i16
+                && !response.path_dependent
             {
-                value = alice_response.value;
-                quality = alice_response.quality;
+                // Update the value of Bob's response by evaluating Alice's responses to it. If Alice has no response, then
+                // leave the response's value and quality as is.
+                if let Some(alice_response) =
+                    alice_search(context, &response.state, alpha, beta, response_depth, max_depth + extension)
+                {
+                    value = alice_response.value;
+                    quality = alice_response.quality;
+                    response_path_dependent = alice_response.path_dependent;
+                }
             }
-        }
 
-        // Determine if this response's value is the best so far. If so, then save the value and do alpha-beta pruning
-        if value < best_value {
-            // Save it
-            best_state = Some(&response.state);
-            best_value = value;
-            best_quality = quality;
+            // If the value of any response depends on the path, then so does the value of this state
+            path_dependent |= response_path_dependent;
 
-            // If Bob wins with this response, then there is no reason to look for anything better
-            if best_value <= context.sef.bob_wins_value() {
-                break;
-            }
+            // Determine if this response's value is the best so far. If so, then save the value and do alpha-beta pruning
+            if best_state.is_none() || value.total_cmp(&best_value).is_lt() {
+                // Save it
+                best_state = Some(response.state);
+                best_value = value;
+                best_quality = quality;
 
-            // alpha-beta pruning (alpha cutoff) Here's how it works:
-            //
-            // Alice is looking for the highest value. The 'alpha' is the value of Alice's best move found so far in the previous
-            // ply. If the value of this response is lower than the alpha, then Alice will never choose a response leading to this
-            // response because the result is worse than the result of a response Alice has already found. As such, there is no
-            // reason to continue.
-
-            if best_value < alpha {
-                // Alpha cutoff
-                pruned = true;
-                break;
-            }
+                // If Bob wins with this response, then there is no reason to look for anything better
+                if best_value.total_cmp(&context.sef.bob_wins_value()).is_le() {
+                    break 'stages;
+                }
+
+                // alpha-beta pruning (alpha cutoff) Here's how it works:
+                //
+                // Alice is looking for the highest value. The 'alpha' is the value of Alice's best move found so far in the
+                // previous ply. If the value of this response is lower than the alpha, then Alice will never choose a response
+                // leading to this response because the result is worse than the result of a response Alice has already found.
+                // As such, there is no reason to continue.
+
+                if best_value.total_cmp(&alpha).is_lt() {
+                    // Alpha cutoff
+                    pruned = true;
+                    break 'stages;
+                }
 
-            // alpha-beta pruning (beta) Here's how it works:
-            //
-            // Bob is looking for the lowest value. The 'beta' is the value of Bob's best response found so far. If the
-            // value of this response is lower than the beta, then it is a better response for Bob. The beta is subsequently passed
-            // to Alice's search so that if Alice finds a response with a higher value than the beta, then there is no reason to
-            // continue because Bob already has a better response and will choose it instead of allowing Alice to make a move with
-            // a higher value.
-            if best_value < beta {
-                beta = best_value;
+                // alpha-beta pruning (beta) Here's how it works:
+                //
+                // Bob is looking for the lowest value. The 'beta' is the value of Bob's best response found so far. If the
+                // value of this response is lower than the beta, then it is a better response for Bob. The beta is subsequently
+                // passed to Alice's search so that if Alice finds a response with a higher value than the beta, then there is
+                // no reason to continue because Bob already has a better response and will choose it instead of allowing Alice
+                // to make a move with a higher value.
+                if best_value.total_cmp(&beta).is_lt() {
+                    beta = best_value;
+                }
             }
         }
     }
 
-    assert!(best_value < f32::INFINITY); // Sanity check
+    // If there are no responses, return without a response. It's up to the caller to decide how to handle this case.
+    let best_state = best_state?;
     assert!(best_quality >= 0); // Sanity check
-    assert!(best_state.is_some()); // Sanity check
-
-    // Just in case
-    best_state.as_ref()?;
 
     // At this point, the value of this state becomes the value of the best response to it, and the quality becomes its
     // quality + 1.
     //
     // Save the value of this state in the T-table if the ply was not pruned. Pruning results in an incorrect value because the
-    // search was interrupted and potentially better responses were not considered.
-    if !pruned {
-        context.tt.borrow_mut().update(state.fingerprint(), best_value, best_quality + 1);
+    // search was interrupted and potentially better responses were not considered. A value that depends on the path to this
+    // state is not saved either, because it is not necessarily the value of the state when reached by a different path. Nor is
+    // the value found by a search with a null window, which is only a bound.
+    if !pruned && !path_dependent && !null_window {
+        context
+            .tt
+            .borrow_mut()
+            .update_with_key(
+                state.fingerprint(),
+                state.verification_key(),
+                shift_win_value(context.sef, context.win_distance, best_value, -depth),
+                best_quality + 1,
+            );
     }
 
-    Some(Response::<S> {
-        state: Rc::clone(best_state?),
+    Some(Response::<S, V> {
+        state: best_state,
         value: best_value,
         quality: best_quality + 1,
+        path_dependent,
     })
 }
 
-// Generates a list of responses to the given node
-fn generate_responses<S, E, R>(
-    context: &Context<S, E, R>,
-    state: &Rc<S>,
+// Returns the maximum depth of a reduced-depth search of the state if it should be searched to a reduced depth before it is
+// searched to the full depth. That is the case if internal iterative deepening is enabled, the remaining depth is sufficient,
+// and the state has no searched value in the T-table.
+fn iid_max_depth<S, V, E, R>(context: &Context<S, V, E, R>, state: &Rc<S>, depth: i32, max_depth: i32) -> Option<i32>
+where
+    S: State,
+    V: Score,
+    E: StaticEvaluator<S, V>,
+    R: ResponseGenerator<State = S>,
+{
+    let iid = context.iid?;
+    let searched = || {
+        context
+            .tt
+            .borrow_mut()
+            .check_with_key(state.fingerprint(), state.verification_key(), SEF_QUALITY + 1)
+            .is_some()
+    };
+    if iid.reduction <= 0 || max_depth - depth < iid.min_depth || searched() {
+        return None;
+    }
+    Some(max_depth - iid.reduction)
+}
+
+// Returns true if the first of the sorted responses is singular and can be extended. See SingularExtensions.
+fn is_singular<S, V, E, R>(
+    context: &Context<S, V, E, R>,
+    responses: &[Response<S, V>],
+    player: PlayerId,
+    depth: i32,
+    max_depth: i32,
+) -> bool
+where
+    S: State,
+    V: Score,
+    E: StaticEvaluator<S, V>,
+    R: ResponseGenerator<State = S>,
+{
+    let Some(singular) = context.singular else {
+        return false;
+    };
+    let Some(candidate) = responses.first() else {
+        return false;
+    };
+
+    // The candidate must be the result of a previous search, and the search must be allowed to be extended
+    if candidate.quality <= SEF_QUALITY
+        || candidate.path_dependent
+        || max_depth - depth < singular.min_depth
+        || max_depth >= context.max_extended_depth
+    {
+        return false;
+    }
+
+    // Search each of the alternatives to a reduced depth, with a window that only determines if it is better or worse than the
+    // candidate's value less the margin. The search stops as soon as an alternative is not worse.
+    let response_depth = depth + 1;
+    let reduced_max_depth = max_depth - singular.reduction;
+    let singular = match player {
+        PlayerId::ALICE => {
+            let threshold = candidate.value.saturating_sub(singular.margin);
+            responses[1..].iter().all(|alternative| {
+                let mut value = alternative.value;
+                if response_depth < reduced_max_depth
+                    && !alternative.path_dependent
+                    && let Some(response) =
+                        bob_search(context, &alternative.state, threshold, V::MAX, response_depth, reduced_max_depth)
+                {
+                    value = response.value;
+                }
+                value.total_cmp(&threshold).is_lt()
+            })
+        }
+        PlayerId::BOB => {
+            let threshold = candidate.value.saturating_add(singular.margin);
+            responses[1..].iter().all(|alternative| {
+                let mut value = alternative.value;
+                if response_depth < reduced_max_depth
+                    && !alternative.path_dependent
+                    && let Some(response) =
+                        alice_search(context, &alternative.state, V::MIN, threshold, response_depth, reduced_max_depth)
+                {
+                    value = response.value;
+                }
+                value.total_cmp(&threshold).is_gt()
+            })
+        }
+    };
+
+    if singular {
+        context.statistics.borrow_mut().singular_extensions += 1;
+    }
+    singular
+}
+
+// Converts a stage of generated responses to the state at the given depth to a list of responses with preliminary values
+fn generate_responses<S, V, E, R>(
+    context: &Context<S, V, E, R>,
+    stage: Vec<Box<S>>,
     depth: i32,
-) -> Vec<Response<S>>
+    max_depth: i32,
+) -> Vec<Response<S, V>>
 where
     S: State,
-    E: StaticEvaluator<S>,
+    V: Score,
+    E: StaticEvaluator<S, V>,
     R: ResponseGenerator<State = S>,
 {
-    // Handle the case where node.state might be None
-    let responses = context.rg.generate(state, depth);
-    responses
+    stage
         .into_iter()
         .map(|state| {
-            let rc_state = Rc::from(state);
-            let (value, quality) = get_preliminary_value(context, &rc_state);
-            Response::<S> {
+            let rc_state: Rc<S> = Rc::from(state);
+
+            // A response that repeats a state on the path is not evaluated. Its value depends on the path.
+            if context.path.borrow().contains(&rc_state.fingerprint()) {
+                let value = context.sef.repetition_value(&rc_state).unwrap_or(context.draw_value);
+                return Response::<S, V> {
+                    state: rc_state,
+                    value,
+                    quality: SEF_QUALITY,
+                    path_dependent: true,
+                };
+            }
+
+            // A drawn response is not evaluated. With contempt, its value depends on the player to move at the root.
+            if rc_state.is_terminal() && rc_state.is_draw() {
+                return Response::<S, V> {
+                    state: rc_state,
+                    value: context.draw_value,
+                    quality: SEF_QUALITY,
+                    path_dependent: context.contempt,
+                };
+            }
+
+            // A response whose result is known is not evaluated or searched. Its value is exact, so its quality is the quality
+            // of a search of it.
+            if let Some((value, draw)) =
+                oracle_value(context.sef, context.win_distance, context.draw_value, &*rc_state, depth + 1)
+            {
+                context.statistics.borrow_mut().oracle_hits += 1;
+                return Response::<S, V> {
+                    state: rc_state,
+                    value,
+                    quality: (max_depth - depth - 1).max(0) as i16,
+                    path_dependent: draw && context.contempt,
+                };
+            }
+
+            let (value, quality) = get_preliminary_value(context, &rc_state, depth + 1);
+            Response::<S, V> {
                 state: rc_state,
                 value,
                 quality,
+                path_dependent: false,
             }
         })
         .collect()
 }
 
+// Returns true if the values of wins known by the static evaluator's oracle reflect the number of plies to the end of the game.
+// That requires the win values to be more than MAX_WIN_PLIES from the draw value (and so from each other), so that the value of
+// a win can be moved by up to MAX_WIN_PLIES - 1 without reaching the draw value or the other player's win value.
+fn scores_win_distance<S, V, E>(sef: &E) -> bool
+where
+    V: Score,
+    E: StaticEvaluator<S, V>,
+{
+    let band = V::from_f32(MAX_WIN_PLIES as f32);
+    let draw_value = sef.draw_value();
+    sef.oracle().is_some()
+        && sef.alice_wins_value().saturating_sub(band) > draw_value
+        && sef.bob_wins_value().saturating_add(band) < draw_value
+}
+
+// Returns the value of the state at the given depth if the static evaluator's oracle knows its result, and whether the result
+// is a draw. If win_distance is true, the value of a win is reduced (or increased for Bob) by the number of plies from the root
+// to the end of the game, so that quicker wins are preferred and slower losses are preferred.
+fn oracle_value<S, V, E>(sef: &E, win_distance: bool, draw_value: V, state: &S, depth: i32) -> Option<(V, bool)>
+where
+    V: Score,
+    E: StaticEvaluator<S, V>,
+{
+    let solution = sef.oracle()?.probe(state)?;
+    let plies = if win_distance {
+        V::from_f32((depth + i32::from(solution.distance)).min(MAX_WIN_PLIES - 1) as f32)
+    } else {
+        V::default()
+    };
+    let value = match solution.result {
+        GameResult::AliceWins => sef.alice_wins_value().saturating_sub(plies),
+        GameResult::BobWins => sef.bob_wins_value().saturating_add(plies),
+        GameResult::Draw => draw_value,
+    };
+    Some((value, solution.result == GameResult::Draw))
+}
+
+// Returns the value moved the given number of plies further from the end of the game (or closer if negative) if it is the
+// value of a win that is known by an oracle and win_distance is true. Such a value is relative to the root, so it is stored in
+// the T-table relative to the state (shifted by minus the state's depth) and restored when probed (shifted by the state's
+// depth).
+fn shift_win_value<S, V, E>(sef: &E, win_distance: bool, value: V, plies: i32) -> V
+where
+    V: Score,
+    E: StaticEvaluator<S, V>,
+{
+    if plies == 0 || !win_distance {
+        return value;
+    }
+    let alice_wins = sef.alice_wins_value();
+    let bob_wins = sef.bob_wins_value();
+    let band = V::from_f32(MAX_WIN_PLIES as f32);
+    let shift = V::from_f32(plies as f32);
+    if value < alice_wins && value > alice_wins.saturating_sub(band) {
+        value.saturating_sub(shift)
+    } else if value > bob_wins && value < bob_wins.saturating_add(band) {
+        value.saturating_add(shift)
+    } else {
+        value
+    }
+}
+
 // Get a preliminary value of the state from the static evaluator or the transposition table
-fn get_preliminary_value<S, E, R>(
-    context: &Context<S, E, R>,
+fn get_preliminary_value<S, V, E, R>(
+    context: &Context<S, V, E, R>,
     state: &Rc<S>,
-) -> (f32, i16)
+    depth: i32,
+) -> (V, i16)
 where
     S: State,
-    E: StaticEvaluator<S>,
+    V: Score,
+    E: StaticEvaluator<S, V>,
     R: ResponseGenerator<State = S>,
 {
     // SEF optimization:
@@ -490,17 +1136,19 @@ where
 
     // If it is in the T-table then use that value, otherwise evaluate the state and save the value.
     let fingerprint = state.fingerprint();
+    let key = state.verification_key();
 
     // First, check if the value is in the transposition table
-    if let Some(cached_value) = context.tt.borrow_mut().check(fingerprint, -1) {
-        return cached_value;
+    if let Some((value, quality)) = context.tt.borrow_mut().check_with_key(fingerprint, key, -1) {
+        return (shift_win_value(context.sef, context.win_distance, value, depth), quality);
     }
 
     // Value not in table, so evaluate with static evaluator and store result
     let value = context.sef.evaluate(state);
+    context.statistics.borrow_mut().evaluations += 1;
     context
         .tt
         .borrow_mut()
-        .update(fingerprint, value, SEF_QUALITY);
+        .update_with_key(fingerprint, key, shift_win_value(context.sef, context.win_distance, value, -depth), SEF_QUALITY);
     (value, SEF_QUALITY)
 }
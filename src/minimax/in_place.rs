@@ -0,0 +1,530 @@
+//! Make/Unmake Minimax Search Implementation
+//!
+//! This module implements the same search as the [`minimax`](crate::minimax) module, except that the tree is walked with a
+//! single mutable state. Instead of creating a new state for each response, an action is applied to the state before its
+//! response is searched and undone afterwards. This avoids allocating a state for every node, which can dominate the cost of
+//! the search for games with large states.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use std::cell::RefCell;
+//! use std::rc::Rc;
+//! use crate::minimax::in_place::search;
+//! use crate::transposition_table::TranspositionTable;
+//!
+//! // Assuming you have implemented the required traits for your game
+//! let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+//! let static_evaluator = MyStaticEvaluator::new();
+//! let action_generator = MyActionGenerator::new();
+//! let mut state = MyGameState::new();
+//!
+//! if let Some(best_action) = search(&tt, &static_evaluator, &action_generator, &mut state, 6) {
+//!     state.apply_in_place(&best_action);
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{Ancestor, Config, SEF_QUALITY, contempt_draw_value, oracle_value, scores_win_distance, shift_win_value};
+use crate::score::Score;
+use crate::state::*;
+use crate::static_evaluator::*;
+use crate::transposition_table::*;
+
+/// A trait for states that can be changed in place.
+///
+/// Applying an action and then undoing the same action must restore the state exactly, including its fingerprint. If undoing
+/// an action requires information that is not in the state (e.g. a captured piece), then the action must carry it.
+///
+/// # Examples
+///
+/// ```rust
+/// # use game_player::State;
+/// # use game_player::minimax::in_place::UndoableState;
+/// #[derive(Clone, Copy)]
+/// struct Counter {
+///     total: u32,
+///     player: u8,
+/// }
+///
+/// impl State for Counter {
+///     type Action = u32;
+///     fn fingerprint(&self) -> u64 { (self.total as u64) << 1 | self.player as u64 }
+///     fn whose_turn(&self) -> u8 { self.player }
+///     fn is_terminal(&self) -> bool { self.total >= 21 }
+///     fn apply(&self, action: &u32) -> Self {
+///         let mut next = *self;
+///         next.apply_in_place(action);
+///         next
+///     }
+/// }
+///
+/// impl UndoableState for Counter {
+///     fn apply_in_place(&mut self, action: &u32) {
+///         self.total += action;
+///         self.player = 1 - self.player;
+///     }
+///
+///     fn undo(&mut self, action: &u32) {
+///         self.total -= action;
+///         self.player = 1 - self.player;
+///     }
+/// }
+///
+/// let mut counter = Counter { total: 10, player: 0 };
+/// counter.apply_in_place(&3);
+/// assert_eq!(counter.total, 13);
+/// counter.undo(&3);
+/// assert_eq!(counter.total, 10);
+/// assert_eq!(counter.whose_turn(), 0);
+/// ```
+pub trait UndoableState: State {
+    /// Applies an action to this state.
+    ///
+    /// # Arguments
+    /// * `action` - The action to apply
+    fn apply_in_place(&mut self, action: &Self::Action);
+
+    /// Undoes an action that was the last action applied to this state.
+    ///
+    /// # Arguments
+    /// * `action` - The action to undo
+    fn undo(&mut self, action: &Self::Action);
+}
+
+/// Action generator function object trait.
+///
+/// This is the counterpart of [`ResponseGenerator`](super::ResponseGenerator) for the make/unmake search. It returns the
+/// actions available to the player to move instead of the resulting states.
+///
+/// # Implementation Notes
+/// - Return an empty vector if no actions are available (player cannot respond)
+/// - If passing is allowed in the game, include a "pass" action
+/// - The state must be unchanged when this returns
+pub trait ActionGenerator {
+    /// The type representing game states that this generator works with
+    type State: UndoableState;
+
+    /// Generates a list of all possible actions by the player to move in the given state.
+    ///
+    /// # Arguments
+    /// * `state` - The current state to generate actions for
+    /// * `depth` - Current search depth (ply number), useful for optimizations
+    ///
+    /// # Returns
+    /// A vector of all possible actions. Returns an empty vector if no actions are available.
+    fn generate(&self, state: &Self::State, depth: i32) -> Vec<<Self::State as State>::Action>;
+}
+
+// Holds evaluation information about a response.
+struct Response<A, V> {
+    // The action resulting in the response
+    action: A,
+    // Value of the resulting state
+    value: V,
+    // Quality of the value. Quality is the number of plies searched to find the value.
+    quality: i16,
+    // True if the value depends on the path to the state (i.e. a repetition was found) or on the player to move at the root
+    // (i.e. a draw was found and there is contempt), and must not be saved in the T-table
+    path_dependent: bool,
+}
+
+// Holds static information pertaining to the search.
+struct Context<'a, S, V, E: StaticEvaluator<S, V>, G: ActionGenerator<State = S>>
+where
+    S: UndoableState,
+    V: Score,
+{
+    max_depth: i32,
+    ag: &'a G,
+    sef: &'a E,
+    tt: &'a Rc<RefCell<TranspositionTable<V>>>,
+    // Value of a draw, adjusted by the contempt for the player to move at the root
+    draw_value: V,
+    // True if the value of a draw depends on the player to move at the root (i.e. there is contempt)
+    contempt: bool,
+    // True if the value of a win known by an oracle reflects the number of plies to the end of the game
+    win_distance: bool,
+    // Fingerprints of the game history and of the states from the root to the state being searched
+    path: RefCell<Vec<u64>>,
+    _phantom: std::marker::PhantomData<S>,
+}
+
+/// A minimax search that applies and undoes actions on a single state.
+///
+/// The result is the same as the result of [`minimax::search`](super::search), except that the best action is returned
+/// instead of the resulting state.
+///
+/// # Type Parameters
+/// * `S` - Game state type that implements the `UndoableState` trait
+/// * `V` - Value type that implements the `Score` trait (`f32` by default)
+/// * `E` - Static evaluator type that implements `StaticEvaluator<S, V>`
+/// * `G` - Action generator type that implements `ActionGenerator<S>`
+///
+/// # Arguments
+/// * `tt` - A transposition table for caching previously computed positions. Can be reused across multiple searches.
+/// * `sef` - The static evaluation function
+/// * `ag` - The action generator
+/// * `s0` - The state to search from. It is restored before this function returns.
+/// * `max_depth` - Maximum search depth in plies
+///
+/// # Returns
+/// `Some(action)` containing the best action found, or `None` if no actions are available
+pub fn search<S, V, E, G>(
+    tt: &Rc<RefCell<TranspositionTable<V>>>,
+    sef: &E,
+    ag: &G,
+    s0: &mut S,
+    max_depth: i32,
+) -> Option<S::Action>
+where
+    S: UndoableState,
+    V: Score,
+    E: StaticEvaluator<S, V>,
+    G: ActionGenerator<State = S>,
+{
+    search_with_config(tt, sef, ag, s0, max_depth, &Config::default())
+}
+
+/// A minimax search that applies and undoes actions on a single state, with the given options.
+///
+/// This is the same as [`search`], except that the search is configured by `config`. Draws and repetitions are handled as
+/// described in [`minimax::search_with_config`](super::search_with_config). Internal iterative deepening (`config.iid`),
+/// singular extensions (`config.singular`), and ProbCut (`config.probcut`) are not supported, and must not be enabled.
+///
+/// # Arguments
+/// * `tt` - A transposition table for caching previously computed positions. Can be reused across multiple searches.
+/// * `sef` - The static evaluation function
+/// * `ag` - The action generator
+/// * `s0` - The state to search from. It is restored before this function returns.
+/// * `max_depth` - Maximum search depth in plies
+/// * `config` - Search options
+///
+/// # Returns
+/// `Some(action)` containing the best action found, or `None` if no actions are available
+///
+/// # Panics
+///
+/// Panics if internal iterative deepening, singular extensions, or ProbCut are enabled.
+pub fn search_with_config<S, V, E, G>(
+    tt: &Rc<RefCell<TranspositionTable<V>>>,
+    sef: &E,
+    ag: &G,
+    s0: &mut S,
+    max_depth: i32,
+    config: &Config<V>,
+) -> Option<S::Action>
+where
+    S: UndoableState,
+    V: Score,
+    E: StaticEvaluator<S, V>,
+    G: ActionGenerator<State = S>,
+{
+    assert!(config.singular.is_none(), "singular extensions are not supported by the make/unmake search");
+    assert!(config.iid.is_none(), "internal iterative deepening is not supported by the make/unmake search");
+    assert!(config.probcut.is_none(), "ProbCut is not supported by the make/unmake search");
+    let context = Context {
+        tt,
+        sef,
+        ag,
+        max_depth,
+        draw_value: contempt_draw_value(sef, s0.whose_turn(), config.contempt),
+        contempt: config.contempt != V::default(),
+        win_distance: scores_win_distance(sef),
+        path: RefCell::new(config.history.clone()),
+        _phantom: std::marker::PhantomData,
+    };
+    let response = if s0.whose_turn() == PlayerId::ALICE as u8 {
+        alice_search(&context, s0, V::MIN, V::MAX, 0)
+    } else {
+        bob_search(&context, s0, V::MIN, V::MAX, 0)
+    };
+    response.map(|response| response.action)
+}
+
+// Evaluates all of Alice's possible responses to the given state. The returned response is the one with the highest value.
+// The state is restored before returning.
+fn alice_search<S, V, E, G>(
+    context: &Context<S, V, E, G>,
+    state: &mut S,
+    mut alpha: V,
+    beta: V,
+    depth: i32,
+) -> Option<Response<S::Action, V>>
+where
+    S: UndoableState,
+    V: Score,
+    E: StaticEvaluator<S, V>,
+    G: ActionGenerator<State = S>,
+{
+    let response_depth = depth + 1;
+    let search_quality = (context.max_depth - response_depth) as i16;
+    let fingerprint = state.fingerprint();
+    let key = state.verification_key();
+
+    // This state is an ancestor of all states searched from here until this function returns.
+    let _ancestor = Ancestor::push(&context.path, fingerprint);
+
+    let mut responses = generate_responses(context, state, depth);
+    if responses.is_empty() {
+        return None;
+    }
+
+    // Sort from highest to lowest in order to increase the chance of triggering a beta cutoff earlier.
+    responses.sort_by(|a, b| b.value.total_cmp(&a.value));
+
+    let mut best: Option<usize> = None;
+    let mut best_value = V::MIN;
+    let mut best_quality = -1;
+    let mut pruned = false;
+    let mut path_dependent = false;
+
+    for (i, response) in responses.iter().enumerate() {
+        // Replace the preliminary value and quality of this response with the value and quality of Bob's subsequent response
+        // to it, under the same conditions as the minimax search.
+        let mut value = response.value;
+        let mut quality = response.quality;
+        let mut response_path_dependent = response.path_dependent;
+        if value.total_cmp(&context.sef.alice_wins_value()).is_lt()
+            && response_depth < context.max_depth
+            && quality < search_quality
+            && !response.path_dependent
+        {
+            state.apply_in_place(&response.action);
+            let bob_response = bob_search(context, state, alpha, beta, response_depth);
+            state.undo(&response.action);
+
+            if let Some(bob_response) = bob_response {
+                value = bob_response.value;
+                quality = bob_response.quality;
+                response_path_dependent = bob_response.path_dependent;
+            }
+        }
+
+        path_dependent |= response_path_dependent;
+
+        if best.is_none() || value.total_cmp(&best_value).is_gt() {
+            best = Some(i);
+            best_value = value;
+            best_quality = quality;
+
+            // If Alice wins with this response, then there is no reason to look for anything better
+            if best_value.total_cmp(&context.sef.alice_wins_value()).is_ge() {
+                break;
+            }
+
+            // Beta cutoff
+            if best_value.total_cmp(&beta).is_gt() {
+                pruned = true;
+                break;
+            }
+
+            if best_value.total_cmp(&alpha).is_gt() {
+                alpha = best_value;
+            }
+        }
+    }
+
+    let best = best?;
+    assert!(best_quality >= 0); // Sanity check
+
+    // Save the value of this state in the T-table if the ply was not pruned and the value does not depend on the path.
+    if !pruned && !path_dependent {
+        let value = shift_win_value(context.sef, context.win_distance, best_value, -depth);
+        context.tt.borrow_mut().update_with_key(fingerprint, key, value, best_quality + 1);
+    }
+
+    let action = responses.swap_remove(best).action;
+    Some(Response {
+        action,
+        value: best_value,
+        quality: best_quality + 1,
+        path_dependent,
+    })
+}
+
+// Evaluates all of Bob's possible responses to the given state. The returned response is the one with the lowest value.
+// The state is restored before returning.
+fn bob_search<S, V, E, G>(
+    context: &Context<S, V, E, G>,
+    state: &mut S,
+    alpha: V,
+    mut beta: V,
+    depth: i32,
+) -> Option<Response<S::Action, V>>
+where
+    S: UndoableState,
+    V: Score,
+    E: StaticEvaluator<S, V>,
+    G: ActionGenerator<State = S>,
+{
+    let response_depth = depth + 1;
+    let search_quality = (context.max_depth - response_depth) as i16;
+    let fingerprint = state.fingerprint();
+    let key = state.verification_key();
+
+    // This state is an ancestor of all states searched from here until this function returns.
+    let _ancestor = Ancestor::push(&context.path, fingerprint);
+
+    let mut responses = generate_responses(context, state, depth);
+    if responses.is_empty() {
+        return None;
+    }
+
+    // Sort from lowest to highest in order to increase the chance of triggering an alpha cutoff earlier
+    responses.sort_by(|a, b| a.value.total_cmp(&b.value));
+
+    let mut best: Option<usize> = None;
+    let mut best_value = V::MAX;
+    let mut best_quality = -1;
+    let mut pruned = false;
+    let mut path_dependent = false;
+
+    for (i, response) in responses.iter().enumerate() {
+        // Replace the preliminary value and quality of this response with the value and quality of Alice's subsequent
+        // response to it, under the same conditions as the minimax search.
+        let mut value = response.value;
+        let mut quality = response.quality;
+        let mut response_path_dependent = response.path_dependent;
+        if value.total_cmp(&context.sef.bob_wins_value()).is_gt()
+            && response_depth < context.max_depth
+            && quality < search_quality
+            && !response.path_dependent
+        {
+            state.apply_in_place(&response.action);
+            let alice_response = alice_search(context, state, alpha, beta, response_depth);
+            state.undo(&response.action);
+
+            if let Some(alice_response) = alice_response {
+                value = alice_response.value;
+                quality = alice_response.quality;
+                response_path_dependent = alice_response.path_dependent;
+            }
+        }
+
+        path_dependent |= response_path_dependent;
+
+        if best.is_none() || value.total_cmp(&best_value).is_lt() {
+            best = Some(i);
+            best_value = value;
+            best_quality = quality;
+
+            // If Bob wins with this response, then there is no reason to look for anything better
+            if best_value.total_cmp(&context.sef.bob_wins_value()).is_le() {
+                break;
+            }
+
+            // Alpha cutoff
+            if best_value.total_cmp(&alpha).is_lt() {
+                pruned = true;
+                break;
+            }
+
+            if best_value.total_cmp(&beta).is_lt() {
+                beta = best_value;
+            }
+        }
+    }
+
+    let best = best?;
+    assert!(best_quality >= 0); // Sanity check
+
+    // Save the value of this state in the T-table if the ply was not pruned and the value does not depend on the path.
+    if !pruned && !path_dependent {
+        let value = shift_win_value(context.sef, context.win_distance, best_value, -depth);
+        context.tt.borrow_mut().update_with_key(fingerprint, key, value, best_quality + 1);
+    }
+
+    let action = responses.swap_remove(best).action;
+    Some(Response {
+        action,
+        value: best_value,
+        quality: best_quality + 1,
+        path_dependent,
+    })
+}
+
+// Generates a list of responses to the given state with preliminary values. Each action is applied in order to evaluate the
+// resulting state, and then undone.
+fn generate_responses<S, V, E, G>(
+    context: &Context<S, V, E, G>,
+    state: &mut S,
+    depth: i32,
+) -> Vec<Response<S::Action, V>>
+where
+    S: UndoableState,
+    V: Score,
+    E: StaticEvaluator<S, V>,
+    G: ActionGenerator<State = S>,
+{
+    let actions = context.ag.generate(state, depth);
+    actions
+        .into_iter()
+        .map(|action| {
+            state.apply_in_place(&action);
+
+            // A response that repeats a state on the path is not evaluated. Its value depends on the path.
+            let response = if context.path.borrow().contains(&state.fingerprint()) {
+                let value = context.sef.repetition_value(state).unwrap_or(context.draw_value);
+                Response {
+                    action,
+                    value,
+                    quality: SEF_QUALITY,
+                    path_dependent: true,
+                }
+            } else if state.is_terminal() && state.is_draw() {
+                // A drawn response is not evaluated. With contempt, its value depends on the player to move at the root.
+                Response {
+                    action,
+                    value: context.draw_value,
+                    quality: SEF_QUALITY,
+                    path_dependent: context.contempt,
+                }
+            } else if let Some((value, draw)) =
+                oracle_value(context.sef, context.win_distance, context.draw_value, state, depth + 1)
+            {
+                // A response whose result is known is not evaluated or searched. Its value is exact, so its quality is the
+                // quality of a search of it.
+                Response {
+                    action,
+                    value,
+                    quality: (context.max_depth - depth - 1).max(0) as i16,
+                    path_dependent: draw && context.contempt,
+                }
+            } else {
+                let (value, quality) = get_preliminary_value(context, state, depth + 1);
+                Response {
+                    action,
+                    value,
+                    quality,
+                    path_dependent: false,
+                }
+            };
+
+            state.undo(&response.action);
+            response
+        })
+        .collect()
+}
+
+// Get a preliminary value of the state from the static evaluator or the transposition table
+fn get_preliminary_value<S, V, E, G>(context: &Context<S, V, E, G>, state: &S, depth: i32) -> (V, i16)
+where
+    S: UndoableState,
+    V: Score,
+    E: StaticEvaluator<S, V>,
+    G: ActionGenerator<State = S>,
+{
+    let fingerprint = state.fingerprint();
+    let key = state.verification_key();
+    if let Some((value, quality)) = context.tt.borrow_mut().check_with_key(fingerprint, key, -1) {
+        return (shift_win_value(context.sef, context.win_distance, value, depth), quality);
+    }
+
+    let value = context.sef.evaluate(state);
+    let stored_value = shift_win_value(context.sef, context.win_distance, value, -depth);
+    context.tt.borrow_mut().update_with_key(fingerprint, key, stored_value, SEF_QUALITY);
+    (value, SEF_QUALITY)
+}
@@ -0,0 +1,259 @@
+//! ProbCut Forward Pruning
+//!
+//! ProbCut (Buro, 1995) uses a shallow search to predict the result of a deep search. In many games, the value found by a
+//! deep search is strongly correlated with the value found by a shallow search of the same state, and the relationship is
+//! approximately linear:
+//!
+//! ```text
+//! deep = slope * shallow + intercept + e
+//! ```
+//!
+//! where `e` is normally distributed with a standard deviation of `sigma`. When the search reaches a state with the remaining
+//! depth of the deep search, it first checks with null-window searches to the shallow depth whether the predicted value is
+//! outside the alpha-beta window by more than `threshold * sigma`. If so, then the deep search would most likely result in a
+//! cutoff, so it is skipped.
+//!
+//! The relationship depends on the game, the static evaluator, and the pair of depths. Use [`calibrate`] to fit it from a set
+//! of sample positions.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use game_player::minimax::{search_with_config, Config};
+//! use game_player::minimax::probcut;
+//!
+//! // Fit the model for deep searches of 8 plies predicted by searches of 4 plies
+//! let model = probcut::calibrate(&evaluator, &move_generator, &sample_positions, 8, 4, 1.5, 100000)?.unwrap();
+//! let config = Config { probcut: Some(model), ..Config::default() };
+//! let best_move = search_with_config(&tt, &evaluator, &move_generator, &game_state, 12, &config);
+//! ```
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{Config, Context, ResponseGenerator, Response, Statistics, alice_search, bob_search, search_response};
+use crate::score::Score;
+use crate::state::*;
+use crate::static_evaluator::*;
+use crate::transposition_table::*;
+
+/// ProbCut options, including the linear model that predicts the value of a deep search from the value of a shallow search.
+///
+/// # Examples
+/// ```rust
+/// # use game_player::minimax::probcut::ProbCut;
+/// // Shallow and deep values from sample positions
+/// let samples = [(1.0, 2.0), (2.0, 4.5), (3.0, 5.5), (4.0, 8.0)];
+/// let model = ProbCut::fit(6, 2, 1.5, &samples).unwrap();
+/// assert!((model.slope - 1.9).abs() < 1.0e-4);
+/// assert!((model.predict(2.5) - 5.0).abs() < 1.0e-4);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProbCut {
+    /// The remaining depth, in plies, of the searches that can be pruned
+    pub depth: i32,
+    /// The depth, in plies, of the shallow search
+    pub shallow_depth: i32,
+    /// The slope of the linear relationship between the shallow and deep values
+    pub slope: f32,
+    /// The intercept of the linear relationship between the shallow and deep values
+    pub intercept: f32,
+    /// The standard deviation of the error of the prediction
+    pub sigma: f32,
+    /// The number of standard deviations by which the predicted value must be outside the window in order to prune. A higher
+    /// threshold prunes less often, but makes fewer mistakes.
+    pub threshold: f32,
+}
+
+impl ProbCut {
+    /// Fits the linear model to pairs of shallow and deep values using least squares.
+    ///
+    /// # Arguments
+    /// * `depth` - The depth of the deep searches
+    /// * `shallow_depth` - The depth of the shallow searches
+    /// * `threshold` - The number of standard deviations by which the predicted value must be outside the window
+    /// * `samples` - Pairs of the values of a shallow search and a deep search of the same state
+    ///
+    /// # Returns
+    /// The fitted model, or `None` if there are fewer than 2 samples or all of the shallow values are the same
+    pub fn fit(depth: i32, shallow_depth: i32, threshold: f32, samples: &[(f32, f32)]) -> Option<Self> {
+        if samples.len() < 2 {
+            return None;
+        }
+
+        let n = samples.len() as f64;
+        let mean_x = samples.iter().map(|&(x, _)| x as f64).sum::<f64>() / n;
+        let mean_y = samples.iter().map(|&(_, y)| y as f64).sum::<f64>() / n;
+        let covariance = samples.iter().map(|&(x, y)| (x as f64 - mean_x) * (y as f64 - mean_y)).sum::<f64>();
+        let variance = samples.iter().map(|&(x, _)| (x as f64 - mean_x).powi(2)).sum::<f64>();
+        if variance <= 0.0 {
+            return None;
+        }
+
+        let slope = covariance / variance;
+        let intercept = mean_y - slope * mean_x;
+        let residuals = samples
+            .iter()
+            .map(|&(x, y)| (y as f64 - (slope * x as f64 + intercept)).powi(2))
+            .sum::<f64>();
+
+        Some(Self {
+            depth,
+            shallow_depth,
+            slope: slope as f32,
+            intercept: intercept as f32,
+            sigma: (residuals / n).sqrt() as f32,
+            threshold,
+        })
+    }
+
+    /// Returns the predicted value of a deep search, given the value of a shallow search.
+    pub fn predict(&self, shallow_value: f32) -> f32 {
+        self.slope * shallow_value + self.intercept
+    }
+}
+
+/// Fits a ProbCut model by searching each of the sample positions to the shallow depth and to the deep depth.
+///
+/// Each search uses a new transposition table, so that the results of one search do not affect another.
+///
+/// # Arguments
+/// * `sef` - The static evaluation function
+/// * `rg` - The response generator
+/// * `positions` - The sample positions. They should be typical of the positions in which the search will be used.
+/// * `depth` - The depth of the deep searches
+/// * `shallow_depth` - The depth of the shallow searches
+/// * `threshold` - The number of standard deviations by which the predicted value must be outside the window
+/// * `table_size` - The number of entries in the transposition table used by each search
+///
+/// # Returns
+/// The fitted model, or `None` if it could not be fitted (see [`ProbCut::fit`]). Positions with no responses are ignored.
+///
+/// # Errors
+/// Returns [`TableError::ZeroSize`] if `table_size` is 0, or [`TableError::TooLarge`] if the tables cannot be allocated.
+pub fn calibrate<S, V, E, R>(
+    sef: &E,
+    rg: &R,
+    positions: &[Rc<S>],
+    depth: i32,
+    shallow_depth: i32,
+    threshold: f32,
+    table_size: usize,
+) -> Result<Option<ProbCut>, TableError>
+where
+    S: State,
+    V: Score,
+    E: StaticEvaluator<S, V>,
+    R: ResponseGenerator<State = S>,
+{
+    let value = |s0: &Rc<S>, max_depth: i32| -> Result<Option<f32>, TableError> {
+        let tt = Rc::new(RefCell::new(TranspositionTable::try_new(table_size, 1)?));
+        let response = search_response(&tt, sef, rg, s0, max_depth, &Config::default(), &mut Statistics::default());
+        Ok(response.map(|response| response.value.to_f32()))
+    };
+    let mut samples = Vec::new();
+    for s0 in positions {
+        if let (Some(shallow), Some(deep)) = (value(s0, shallow_depth)?, value(s0, depth)?) {
+            samples.push((shallow, deep));
+        }
+    }
+    Ok(ProbCut::fit(depth, shallow_depth, threshold, &samples))
+}
+
+// If ProbCut is enabled and applies to the state, checks with null-window searches to the shallow depth whether the predicted
+// value of the full search is outside of the window. If so, then the response is returned with a value bounding the predicted
+// value. Otherwise, None is returned and the state must be searched. The values found by the null-window searches are bounds,
+// so they are not saved in the T-table.
+pub(super) fn cut<S, V, E, R>(
+    context: &Context<S, V, E, R>,
+    state: &Rc<S>,
+    player: PlayerId,
+    alpha: V,
+    beta: V,
+    depth: i32,
+    max_depth: i32,
+) -> Option<Response<S, V>>
+where
+    S: State,
+    V: Score,
+    E: StaticEvaluator<S, V>,
+    R: ResponseGenerator<State = S>,
+{
+    let probcut = context.probcut?;
+
+    // The state being searched from is never pruned, since there is no response to return. The model must be increasing in
+    // order to be inverted.
+    if depth == 0 || max_depth - depth != probcut.depth || probcut.shallow_depth >= probcut.depth || probcut.slope <= 0.0 {
+        return None;
+    }
+
+    let shallow_max_depth = depth + probcut.shallow_depth;
+    let margin = probcut.threshold * probcut.sigma;
+    let shallow_search = |bound: V| match player {
+        PlayerId::ALICE => alice_search(context, state, bound, bound, depth, shallow_max_depth),
+        PlayerId::BOB => bob_search(context, state, bound, bound, depth, shallow_max_depth),
+    };
+    // Returns the shallow value that predicts the given deep value
+    let shallow_bound = |deep: f32| V::from_f32((deep - probcut.intercept) / probcut.slope);
+
+    // If the shallow value is above the value that predicts beta plus the margin, then the deep search would fail high.
+    // Otherwise, if it is below the value that predicts alpha less the margin, then the deep search would fail low.
+    let mut cut = None;
+    if beta.total_cmp(&V::MAX).is_lt() {
+        let bound = shallow_bound(beta.to_f32() + margin);
+        let shallow = shallow_search(bound)?;
+        if shallow.value.total_cmp(&bound).is_gt() {
+            let predicted = probcut.predict(shallow.value.to_f32());
+            cut = Some((shallow, predicted - margin));
+        }
+    }
+    if cut.is_none() && alpha.total_cmp(&V::MIN).is_gt() {
+        let bound = shallow_bound(alpha.to_f32() - margin);
+        let shallow = shallow_search(bound)?;
+        if shallow.value.total_cmp(&bound).is_lt() {
+            let predicted = probcut.predict(shallow.value.to_f32());
+            cut = Some((shallow, predicted + margin));
+        }
+    }
+    let (shallow, value) = cut?;
+
+    context.statistics.borrow_mut().probcut_cutoffs += 1;
+    Some(Response {
+        state: shallow.state,
+        value: V::from_f32(value),
+        quality: shallow.quality,
+        path_dependent: shallow.path_dependent,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_exact_line() {
+        let samples = [(-2.0, -3.0), (0.0, 1.0), (1.0, 3.0), (5.0, 11.0)];
+        let model = ProbCut::fit(8, 4, 2.0, &samples).unwrap();
+        assert!((model.slope - 2.0).abs() < 1.0e-5);
+        assert!((model.intercept - 1.0).abs() < 1.0e-5);
+        assert!(model.sigma.abs() < 1.0e-5);
+        assert_eq!((model.depth, model.shallow_depth, model.threshold), (8, 4, 2.0));
+    }
+
+    #[test]
+    fn test_fit_residuals() {
+        // The residuals are +1 and -1
+        let samples = [(0.0, 1.0), (0.0, -1.0), (2.0, 3.0), (2.0, 1.0)];
+        let model = ProbCut::fit(8, 4, 2.0, &samples).unwrap();
+        assert!((model.slope - 1.0).abs() < 1.0e-5);
+        assert!(model.intercept.abs() < 1.0e-5);
+        assert!((model.sigma - 1.0).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_fit_degenerate_samples() {
+        assert!(ProbCut::fit(8, 4, 2.0, &[]).is_none());
+        assert!(ProbCut::fit(8, 4, 2.0, &[(1.0, 2.0)]).is_none());
+        assert!(ProbCut::fit(8, 4, 2.0, &[(1.0, 2.0), (1.0, 3.0)]).is_none());
+    }
+}
@@ -0,0 +1,347 @@
+//! Multi-player Game Tree Search Implementation
+//!
+//! This module implements game tree searches for games with more than two players, using the max^n and paranoid strategies.
+//! The game-specific components are provided by the user using the [`State`] trait, the [`ResponseGenerator`] trait and the
+//! [`VectorEvaluator`] trait defined here.
+//!
+//! # Strategies
+//! - **max^n**: Each player chooses the response that maximizes their own value. This is the natural generalization of
+//!   minimax, but it cannot be pruned with alpha-beta.
+//! - **Paranoid**: The player to move at the root assumes that all other players have formed a coalition against them. This
+//!   reduces the game to a two-player game in which alpha-beta pruning applies.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use std::rc::Rc;
+//! use crate::multiplayer::{max_n_search, paranoid_search};
+//!
+//! // Assuming you have implemented the required traits for your game
+//! let static_evaluator = MyVectorEvaluator::new();
+//! let response_generator = MyResponseGenerator::new();
+//! let initial_state = Rc::new(MyGameState::new());
+//!
+//! let best_move = max_n_search(&static_evaluator, &response_generator, &initial_state, 4);
+//! let safest_move = paranoid_search(&static_evaluator, &response_generator, &initial_state, 6);
+//! ```
+//!
+//! # Notes
+//! - The value of [`State::whose_turn`] is the index of the player to move, from 0 to the number of players - 1.
+//! - The transposition table is not used, because the values depend on the strategy and, for paranoid search, on the player
+//!   to move at the root.
+
+use std::rc::Rc;
+
+use crate::minimax::ResponseGenerator;
+use crate::state::*;
+
+/// An interface for static evaluation functions of multi-player games.
+///
+/// A vector-valued static evaluation function assigns a value for each player to a game state without any lookahead. Each
+/// player seeks to maximize their own value.
+///
+/// The values returned by the static evaluation function should be at most player_wins_value(). If the game is over and a
+/// player has won, then the function should return player_wins_value() as the value for that player.
+///
+/// # Examples
+/// ```rust
+/// # use game_player::multiplayer::VectorEvaluator;
+/// struct Scores;
+///
+/// // A state of a 3-player game is represented here by the players' scores
+/// impl VectorEvaluator<[u32; 3]> for Scores {
+///     fn evaluate(&self, state: &[u32; 3]) -> Vec<f32> {
+///         let total: u32 = state.iter().sum();
+///         state.iter().map(|&score| score as f32 / total.max(1) as f32).collect()
+///     }
+///
+///     fn player_wins_value(&self) -> f32 {
+///         1.0
+///     }
+/// }
+///
+/// assert_eq!(Scores.evaluate(&[2, 1, 1]), vec![0.5, 0.25, 0.25]);
+/// ```
+pub trait VectorEvaluator<G> {
+    /// Evaluates the given state and returns its value for each player.
+    ///
+    /// # Arguments
+    /// * `state` - The state to be evaluated
+    ///
+    /// # Returns
+    /// The value of the state for each player, indexed by player
+    ///
+    /// # Note
+    /// This function must be implemented.
+    fn evaluate(&self, state: &G) -> Vec<f32>;
+
+    /// Returns the value that indicates that a player has won.
+    ///
+    /// # Returns
+    /// The value that indicates that a player has won
+    ///
+    /// # Note
+    /// This function must be implemented.
+    fn player_wins_value(&self) -> f32;
+}
+
+// Holds evaluation information about a response.
+struct Response<S> {
+    // Reference to the resulting state
+    state: Rc<S>,
+    // Values of the state for each player
+    values: Vec<f32>,
+}
+
+// Holds static information pertaining to the search.
+struct Context<'a, S, E: VectorEvaluator<S>, R: ResponseGenerator<State = S>>
+where
+    S: State,
+{
+    max_depth: i32,
+    rg: &'a R,
+    sef: &'a E,
+    _phantom: std::marker::PhantomData<S>,
+}
+
+/// A max^n search for games with any number of players.
+///
+/// Each player is assumed to choose the response that maximizes their own value. Ties are broken in favor of the response
+/// found first.
+///
+/// # Type Parameters
+/// * `S` - Game state type that implements the `State` trait
+/// * `E` - Static evaluator type that implements `VectorEvaluator<S>`
+/// * `R` - Response generator type that implements `ResponseGenerator<S>`
+///
+/// # Arguments
+/// * `sef` - The vector-valued static evaluation function
+/// * `rg` - The response generator
+/// * `s0` - The state to search from
+/// * `max_depth` - Maximum search depth in plies
+///
+/// # Returns
+/// `Some(Rc<S>)` containing the best move found, or `None` if no valid moves exist
+///
+/// # Panics
+/// Panics if the static evaluator does not return a value for the player to move.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use crate::multiplayer::max_n_search;
+///
+/// match max_n_search(&evaluator, &move_generator, &game_state, 4) {
+///     Some(best_move) => println!("Best move: {:?}", best_move),
+///     None => println!("No moves available"),
+/// }
+/// ```
+pub fn max_n_search<S, E, R>(sef: &E, rg: &R, s0: &Rc<S>, max_depth: i32) -> Option<Rc<S>>
+where
+    S: State,
+    E: VectorEvaluator<S>,
+    R: ResponseGenerator<State = S>,
+{
+    let context = Context {
+        sef,
+        rg,
+        max_depth,
+        _phantom: std::marker::PhantomData,
+    };
+    max_n(&context, s0, 0).map(|response| response.state)
+}
+
+/// A paranoid search for games with any number of players.
+///
+/// The player to move in `s0` maximizes their own value and every other player is assumed to minimize it. The search uses
+/// alpha-beta pruning.
+///
+/// # Type Parameters
+/// * `S` - Game state type that implements the `State` trait
+/// * `E` - Static evaluator type that implements `VectorEvaluator<S>`
+/// * `R` - Response generator type that implements `ResponseGenerator<S>`
+///
+/// # Arguments
+/// * `sef` - The vector-valued static evaluation function. Only the values of the player to move in `s0` are used.
+/// * `rg` - The response generator
+/// * `s0` - The state to search from
+/// * `max_depth` - Maximum search depth in plies
+///
+/// # Returns
+/// `Some(Rc<S>)` containing the best move found, or `None` if no valid moves exist
+///
+/// # Panics
+/// Panics if the static evaluator does not return a value for the player to move in `s0`.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use crate::multiplayer::paranoid_search;
+///
+/// match paranoid_search(&evaluator, &move_generator, &game_state, 6) {
+///     Some(best_move) => println!("Safest move: {:?}", best_move),
+///     None => println!("No moves available"),
+/// }
+/// ```
+pub fn paranoid_search<S, E, R>(sef: &E, rg: &R, s0: &Rc<S>, max_depth: i32) -> Option<Rc<S>>
+where
+    S: State,
+    E: VectorEvaluator<S>,
+    R: ResponseGenerator<State = S>,
+{
+    let context = Context {
+        sef,
+        rg,
+        max_depth,
+        _phantom: std::marker::PhantomData,
+    };
+    let root_player = s0.whose_turn() as usize;
+    paranoid(&context, root_player, s0, -f32::INFINITY, f32::INFINITY, 0).map(|(state, _)| state)
+}
+
+// Evaluates all of the possible responses by the player to move in the given state. The returned response is the one with the
+// highest value for that player.
+fn max_n<S, E, R>(context: &Context<S, E, R>, state: &Rc<S>, depth: i32) -> Option<Response<S>>
+where
+    S: State,
+    E: VectorEvaluator<S>,
+    R: ResponseGenerator<State = S>,
+{
+    let player = state.whose_turn() as usize;
+    let response_depth = depth + 1;
+
+    // Generate a list of the possible responses. The responses are initialized with preliminary values.
+    let mut responses = generate_responses(context, state, depth);
+
+    // If there are no responses, return without a response. It's up to the caller to decide how to handle this case.
+    if responses.is_empty() {
+        return None;
+    }
+
+    // Sort from highest to lowest value for the player, so that a win is likely to be found early
+    responses.sort_by(|a, b| b.values[player].total_cmp(&a.values[player]));
+
+    let mut best: Option<Response<S>> = None;
+    for mut response in responses {
+        // Replace the preliminary values with the values of the subsequent response, unless the player has already won or the
+        // search has reached its maximum depth. If there is no subsequent response, leave the values as is.
+        if response.values[player] < context.sef.player_wins_value()
+            && response_depth < context.max_depth
+            && let Some(subsequent) = max_n(context, &response.state, response_depth)
+        {
+            response.values = subsequent.values;
+        }
+
+        if best.as_ref().is_none_or(|best| response.values[player] > best.values[player]) {
+            let wins = response.values[player] >= context.sef.player_wins_value();
+            best = Some(response);
+
+            // If the player wins with this response, then there is no reason to look for anything better
+            if wins {
+                break;
+            }
+        }
+    }
+
+    best
+}
+
+// Evaluates all of the possible responses by the player to move in the given state from the point of view of the root player.
+// If it is the root player's turn, the returned response is the one with the highest value for the root player. Otherwise,
+// it is the one with the lowest value for the root player.
+fn paranoid<S, E, R>(
+    context: &Context<S, E, R>,
+    root_player: usize,
+    state: &Rc<S>,
+    mut alpha: f32,
+    mut beta: f32,
+    depth: i32,
+) -> Option<(Rc<S>, f32)>
+where
+    S: State,
+    E: VectorEvaluator<S>,
+    R: ResponseGenerator<State = S>,
+{
+    let maximizing = state.whose_turn() as usize == root_player;
+    let response_depth = depth + 1;
+    let wins_value = context.sef.player_wins_value();
+
+    // Generate a list of the possible responses, valued from the root player's point of view.
+    let mut responses: Vec<(Rc<S>, f32)> = generate_responses(context, state, depth)
+        .into_iter()
+        .map(|response| (response.state, response.values[root_player]))
+        .collect();
+
+    // If there are no responses, return without a response. It's up to the caller to decide how to handle this case.
+    if responses.is_empty() {
+        return None;
+    }
+
+    // Sort so that the most promising responses are searched first in order to increase the chance of a cutoff
+    responses.sort_by(|a, b| {
+        let order = a.1.total_cmp(&b.1);
+        if maximizing { order.reverse() } else { order }
+    });
+
+    let mut best: Option<(Rc<S>, f32)> = None;
+    for (response, mut value) in responses {
+        // Replace the preliminary value with the value of the subsequent response, unless the root player has already won or
+        // the search has reached its maximum depth.
+        if value < wins_value
+            && response_depth < context.max_depth
+            && let Some((_, subsequent)) = paranoid(context, root_player, &response, alpha, beta, response_depth)
+        {
+            value = subsequent;
+        }
+
+        let is_better = match &best {
+            None => true,
+            Some((_, best_value)) => {
+                if maximizing {
+                    value > *best_value
+                } else {
+                    value < *best_value
+                }
+            }
+        };
+        if !is_better {
+            continue;
+        }
+        best = Some((response, value));
+
+        // Alpha-beta pruning, as in the two-player minimax search. The coalition of the other players takes the role of the
+        // minimizing player.
+        if maximizing {
+            if value >= wins_value || value > beta {
+                break;
+            }
+            alpha = alpha.max(value);
+        } else {
+            if value < alpha {
+                break;
+            }
+            beta = beta.min(value);
+        }
+    }
+
+    best
+}
+
+// Generates a list of responses to the given state, with preliminary values from the static evaluator
+fn generate_responses<S, E, R>(context: &Context<S, E, R>, state: &Rc<S>, depth: i32) -> Vec<Response<S>>
+where
+    S: State,
+    E: VectorEvaluator<S>,
+    R: ResponseGenerator<State = S>,
+{
+    context
+        .rg
+        .generate(state, depth)
+        .into_iter()
+        .map(|state| {
+            let state: Rc<S> = Rc::from(state);
+            let values = context.sef.evaluate(&state);
+            Response { state, values }
+        })
+        .collect()
+}
@@ -0,0 +1,455 @@
+//! Proof-Number Search
+//!
+//! This module implements solvers that prove whether a player can force a win, rather than estimating the value of a state.
+//! The game-specific components are provided by the user using the [`State`] trait, the [`ResponseGenerator`] trait and the
+//! [`ResultEvaluator`] trait defined here.
+//!
+//! # Algorithms
+//! - **Proof-number search** ([`prove`]): The game tree is kept in memory and the most-proving state is expanded repeatedly
+//!   until the root is proven or disproven. Transpositions are not detected, so the memory required grows with the number of
+//!   states expanded.
+//! - **Depth-first proof-number search** ([`df_pn`]): The same states are expanded in depth-first order, using thresholds on
+//!   the proof and disproof numbers. Only the proof and disproof numbers are kept, in a dedicated table indexed by
+//!   fingerprint, so transpositions are detected.
+//!
+//! [`solve`] uses df-pn to determine whether a state is a win for Alice, a win for Bob or a draw.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use std::rc::Rc;
+//! use game_player::pns::{GameResult, solve};
+//!
+//! // Assuming you have implemented the required traits for your game
+//! let result_evaluator = MyResultEvaluator::new();
+//! let response_generator = MyResponseGenerator::new();
+//! let initial_state = Rc::new(MyGameState::new());
+//!
+//! match solve(&result_evaluator, &response_generator, &initial_state, 1000000) {
+//!     Some(GameResult::AliceWins) => println!("Alice can force a win"),
+//!     Some(GameResult::BobWins) => println!("Bob can force a win"),
+//!     Some(GameResult::Draw) => println!("Neither player can force a win"),
+//!     None => println!("Not solved within the limit"),
+//! }
+//! ```
+//!
+//! # Notes
+//! - A state is treated as the end of the game if [`State::is_terminal`] returns true or if the response generator returns no
+//!   responses. The result of the game is then given by the [`ResultEvaluator`].
+//! - The depth passed to the response generator is the number of plies from the root.
+//! - In df-pn, a response that repeats a state in the current line of play does not achieve the goal. The table does not
+//!   account for how a state was reached, so in games with repetitions, a result that depends on a repetition may be wrong
+//!   (the "graph history interaction" problem).
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::minimax::ResponseGenerator;
+use crate::state::*;
+
+/// The result of a game.
+///
+/// # Examples
+/// ```rust
+/// # use game_player::PlayerId;
+/// # use game_player::pns::GameResult;
+/// assert_eq!(GameResult::AliceWins.winner(), Some(PlayerId::ALICE));
+/// assert_eq!(GameResult::Draw.winner(), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameResult {
+    AliceWins,
+    BobWins,
+    Draw,
+}
+
+impl GameResult {
+    /// Returns the player that won, or `None` if the game is a draw
+    pub fn winner(self) -> Option<PlayerId> {
+        match self {
+            GameResult::AliceWins => Some(PlayerId::ALICE),
+            GameResult::BobWins => Some(PlayerId::BOB),
+            GameResult::Draw => None,
+        }
+    }
+}
+
+/// An interface for determining the result of a game that is over.
+///
+/// # Examples
+/// ```rust
+/// # use game_player::pns::{GameResult, ResultEvaluator};
+/// struct LastTakeWins;
+///
+/// // A state of a game of Nim is represented here by the number of remaining stones and the player to move
+/// impl ResultEvaluator<(u32, u8)> for LastTakeWins {
+///     fn result(&self, state: &(u32, u8)) -> GameResult {
+///         // The player that took the last stone won, so the player to move lost
+///         if state.1 == 0 { GameResult::BobWins } else { GameResult::AliceWins }
+///     }
+/// }
+///
+/// assert_eq!(LastTakeWins.result(&(0, 1)), GameResult::AliceWins);
+/// ```
+pub trait ResultEvaluator<G> {
+    /// Returns the result of the game.
+    ///
+    /// # Arguments
+    /// * `state` - A state that is terminal or has no responses
+    ///
+    /// # Returns
+    /// The result of the game
+    ///
+    /// # Note
+    /// This function must be implemented.
+    fn result(&self, state: &G) -> GameResult;
+}
+
+/// The outcome of an attempt to prove that a player can force a win.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Proof {
+    /// The player can force a win
+    Proven,
+    /// The player cannot force a win
+    Disproven,
+    /// The limit was reached before the state was proven or disproven
+    Unknown,
+}
+
+// Proof and disproof numbers of a state that has been proven or disproven
+const INFINITY: u32 = u32::MAX;
+const PROVEN: (u32, u32) = (0, INFINITY);
+const DISPROVEN: (u32, u32) = (INFINITY, 0);
+
+// Returns the proof and disproof numbers of a state in which the game is over
+fn result_numbers<S, E>(re: &E, state: &S, goal: PlayerId) -> (u32, u32)
+where
+    E: ResultEvaluator<S>,
+{
+    if re.result(state).winner() == Some(goal) {
+        PROVEN
+    } else {
+        DISPROVEN
+    }
+}
+
+// Returns the outcome corresponding to the proof and disproof numbers of the root
+fn proof((pn, dn): (u32, u32)) -> Proof {
+    if pn == 0 {
+        Proof::Proven
+    } else if dn == 0 {
+        Proof::Disproven
+    } else {
+        Proof::Unknown
+    }
+}
+
+// A state in the tree of a proof-number search
+struct Node<S> {
+    state: Rc<S>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    pn: u32,
+    dn: u32,
+    // True if it is the goal player's turn
+    or: bool,
+    depth: i32,
+}
+
+/// Attempts to prove that a player can force a win using proof-number search.
+///
+/// # Type Parameters
+/// * `S` - Game state type that implements the `State` trait
+/// * `E` - Result evaluator type that implements `ResultEvaluator<S>`
+/// * `R` - Response generator type that implements `ResponseGenerator<S>`
+///
+/// # Arguments
+/// * `re` - The result evaluator
+/// * `rg` - The response generator
+/// * `s0` - The state to prove
+/// * `goal` - The player that is to win
+/// * `max_nodes` - The maximum number of states kept in the tree
+///
+/// # Returns
+/// `Proof::Proven` if the player can force a win, `Proof::Disproven` if not, or `Proof::Unknown` if the tree reached
+/// `max_nodes` states first
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use game_player::PlayerId;
+/// use game_player::pns::{Proof, prove};
+///
+/// if prove(&result_evaluator, &move_generator, &game_state, PlayerId::ALICE, 100000) == Proof::Proven {
+///     println!("Alice can force a win");
+/// }
+/// ```
+pub fn prove<S, E, R>(re: &E, rg: &R, s0: &Rc<S>, goal: PlayerId, max_nodes: usize) -> Proof
+where
+    S: State,
+    E: ResultEvaluator<S>,
+    R: ResponseGenerator<State = S>,
+{
+    let mut nodes = vec![new_node(re, s0.clone(), None, goal, 0)];
+
+    while nodes[0].pn != 0 && nodes[0].dn != 0 && nodes.len() < max_nodes {
+        // Find the most-proving state. It is the state whose proof would contribute the most to the proof of the root, or
+        // whose disproof would contribute the most to the disproof of the root.
+        let mut index = 0;
+        while !nodes[index].children.is_empty() {
+            let node = &nodes[index];
+            index = if node.or {
+                *node.children.iter().min_by_key(|&&child| nodes[child].pn).unwrap()
+            } else {
+                *node.children.iter().min_by_key(|&&child| nodes[child].dn).unwrap()
+            };
+        }
+
+        // Expand it. If there are no responses, then the game is over.
+        let responses = rg.generate(&nodes[index].state, nodes[index].depth);
+        if responses.is_empty() {
+            (nodes[index].pn, nodes[index].dn) = result_numbers(re, &*nodes[index].state, goal);
+        } else {
+            let depth = nodes[index].depth + 1;
+            for response in responses {
+                let child = nodes.len();
+                nodes.push(new_node(re, Rc::from(response), Some(index), goal, depth));
+                nodes[index].children.push(child);
+            }
+        }
+
+        // Update the proof and disproof numbers of the expanded state and its ancestors
+        let mut next = Some(index);
+        while let Some(index) = next {
+            let node = &nodes[index];
+            if !node.children.is_empty() {
+                let (pn, dn) = combine(node.or, node.children.iter().map(|&child| (nodes[child].pn, nodes[child].dn)));
+                (nodes[index].pn, nodes[index].dn) = (pn, dn);
+            }
+            next = nodes[index].parent;
+        }
+    }
+
+    proof((nodes[0].pn, nodes[0].dn))
+}
+
+// Creates a node for a state that has not been expanded
+fn new_node<S, E>(re: &E, state: Rc<S>, parent: Option<usize>, goal: PlayerId, depth: i32) -> Node<S>
+where
+    S: State,
+    E: ResultEvaluator<S>,
+{
+    let (pn, dn) = if state.is_terminal() {
+        result_numbers(re, &*state, goal)
+    } else {
+        (1, 1)
+    };
+    let or = state.whose_turn() == goal as u8;
+    Node {
+        state,
+        parent,
+        children: Vec::new(),
+        pn,
+        dn,
+        or,
+        depth,
+    }
+}
+
+// Returns the proof and disproof numbers of a state given the proof and disproof numbers of its responses. If it is the goal
+// player's turn, then a single proven response proves the state. Otherwise, every response must be proven.
+fn combine(or: bool, children: impl Iterator<Item = (u32, u32)>) -> (u32, u32) {
+    let (mut min, mut sum) = (INFINITY, 0u32);
+    for (pn, dn) in children {
+        let (phi, delta) = if or { (pn, dn) } else { (dn, pn) };
+        min = min.min(phi);
+        sum = sum.saturating_add(delta);
+    }
+    if or { (min, sum) } else { (sum, min) }
+}
+
+// Holds the static information and the table of a df-pn search.
+struct Context<'a, S, E, R>
+where
+    S: State,
+    E: ResultEvaluator<S>,
+    R: ResponseGenerator<State = S>,
+{
+    re: &'a E,
+    rg: &'a R,
+    goal: PlayerId,
+    max_nodes: usize,
+    nodes: Cell<usize>,
+    // Proof and disproof numbers indexed by fingerprint
+    table: RefCell<HashMap<u64, (u32, u32)>>,
+    // Fingerprints of the states in the current line of play
+    path: RefCell<Vec<u64>>,
+    _phantom: std::marker::PhantomData<S>,
+}
+
+/// Attempts to prove that a player can force a win using depth-first proof-number search (df-pn).
+///
+/// # Type Parameters
+/// * `S` - Game state type that implements the `State` trait
+/// * `E` - Result evaluator type that implements `ResultEvaluator<S>`
+/// * `R` - Response generator type that implements `ResponseGenerator<S>`
+///
+/// # Arguments
+/// * `re` - The result evaluator
+/// * `rg` - The response generator
+/// * `s0` - The state to prove
+/// * `goal` - The player that is to win
+/// * `max_nodes` - The maximum number of states expanded
+///
+/// # Returns
+/// `Proof::Proven` if the player can force a win, `Proof::Disproven` if not, or `Proof::Unknown` if `max_nodes` states were
+/// expanded first
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use game_player::PlayerId;
+/// use game_player::pns::{Proof, df_pn};
+///
+/// if df_pn(&result_evaluator, &move_generator, &game_state, PlayerId::BOB, 1000000) == Proof::Disproven {
+///     println!("Bob cannot force a win");
+/// }
+/// ```
+pub fn df_pn<S, E, R>(re: &E, rg: &R, s0: &Rc<S>, goal: PlayerId, max_nodes: usize) -> Proof
+where
+    S: State,
+    E: ResultEvaluator<S>,
+    R: ResponseGenerator<State = S>,
+{
+    let context = Context {
+        re,
+        rg,
+        goal,
+        max_nodes,
+        nodes: Cell::new(0),
+        table: RefCell::new(HashMap::new()),
+        path: RefCell::new(Vec::new()),
+        _phantom: std::marker::PhantomData,
+    };
+    proof(mid(&context, s0, INFINITY, INFINITY, 0))
+}
+
+/// Determines the result of a game if both players play perfectly.
+///
+/// Alice's win is attempted to be proven first, then Bob's, using df-pn. If neither player can force a win, the result is a
+/// draw.
+///
+/// # Arguments
+/// * `re` - The result evaluator
+/// * `rg` - The response generator
+/// * `s0` - The state to solve
+/// * `max_nodes` - The maximum number of states expanded by each attempt
+///
+/// # Returns
+/// The result of the game, or `None` if it could not be determined within the limit
+pub fn solve<S, E, R>(re: &E, rg: &R, s0: &Rc<S>, max_nodes: usize) -> Option<GameResult>
+where
+    S: State,
+    E: ResultEvaluator<S>,
+    R: ResponseGenerator<State = S>,
+{
+    match df_pn(re, rg, s0, PlayerId::ALICE, max_nodes) {
+        Proof::Proven => return Some(GameResult::AliceWins),
+        Proof::Unknown => return None,
+        Proof::Disproven => {}
+    }
+    match df_pn(re, rg, s0, PlayerId::BOB, max_nodes) {
+        Proof::Proven => Some(GameResult::BobWins),
+        Proof::Disproven => Some(GameResult::Draw),
+        Proof::Unknown => None,
+    }
+}
+
+// Searches the state until its proof number reaches pn_threshold or its disproof number reaches dn_threshold, and returns its
+// proof and disproof numbers. The thresholds are handled in terms of phi and delta, which are the proof and disproof numbers
+// if it is the goal player's turn, and the disproof and proof numbers otherwise.
+fn mid<S, E, R>(context: &Context<S, E, R>, state: &Rc<S>, pn_threshold: u32, dn_threshold: u32, depth: i32) -> (u32, u32)
+where
+    S: State,
+    E: ResultEvaluator<S>,
+    R: ResponseGenerator<State = S>,
+{
+    let fingerprint = state.fingerprint();
+    if state.is_terminal() {
+        let numbers = result_numbers(context.re, &**state, context.goal);
+        context.table.borrow_mut().insert(fingerprint, numbers);
+        return numbers;
+    }
+
+    context.nodes.set(context.nodes.get() + 1);
+    let responses: Vec<Rc<S>> = context.rg.generate(state, depth).into_iter().map(Rc::from).collect();
+    if responses.is_empty() {
+        let numbers = result_numbers(context.re, &**state, context.goal);
+        context.table.borrow_mut().insert(fingerprint, numbers);
+        return numbers;
+    }
+
+    let or = state.whose_turn() == context.goal as u8;
+    let (phi_threshold, delta_threshold) = if or { (pn_threshold, dn_threshold) } else { (dn_threshold, pn_threshold) };
+    let to_phi_delta = |(pn, dn): (u32, u32)| if or { (pn, dn) } else { (dn, pn) };
+
+    context.path.borrow_mut().push(fingerprint);
+    let numbers = loop {
+        // In terms of phi and delta, the phi of this state is the minimum phi of the responses and the delta of this state is
+        // the sum of the delta of the responses.
+        let children: Vec<(u32, u32)> = responses.iter().map(|response| lookup(context, response)).collect();
+        let numbers = combine(or, children.iter().copied());
+        let (phi, delta) = to_phi_delta(numbers);
+        if phi >= phi_threshold || delta >= delta_threshold || context.nodes.get() >= context.max_nodes {
+            break numbers;
+        }
+
+        // Search the most promising response, which is the one with the lowest phi in terms of this state, until it is no
+        // longer the most promising or this state's thresholds would be reached.
+        let (mut best, mut best_phi, mut second_phi) = (0, INFINITY, INFINITY);
+        for (i, &child) in children.iter().enumerate() {
+            let (child_phi, _) = to_phi_delta(child);
+            if child_phi < best_phi {
+                (best, second_phi, best_phi) = (i, best_phi, child_phi);
+            } else if child_phi < second_phi {
+                second_phi = child_phi;
+            }
+        }
+        let (_, best_delta) = to_phi_delta(children[best]);
+        let child_phi_threshold = phi_threshold.min(second_phi.saturating_add(1));
+        let child_delta_threshold = delta_threshold.saturating_sub(delta).saturating_add(best_delta);
+        let (child_pn_threshold, child_dn_threshold) = if or {
+            (child_phi_threshold, child_delta_threshold)
+        } else {
+            (child_delta_threshold, child_phi_threshold)
+        };
+        mid(context, &responses[best], child_pn_threshold, child_dn_threshold, depth + 1);
+    };
+    context.path.borrow_mut().pop();
+
+    context.table.borrow_mut().insert(fingerprint, numbers);
+    numbers
+}
+
+// Returns the proof and disproof numbers of a response
+fn lookup<S, E, R>(context: &Context<S, E, R>, response: &Rc<S>) -> (u32, u32)
+where
+    S: State,
+    E: ResultEvaluator<S>,
+    R: ResponseGenerator<State = S>,
+{
+    let fingerprint = response.fingerprint();
+
+    // A repetition does not achieve the goal
+    if context.path.borrow().contains(&fingerprint) {
+        return DISPROVEN;
+    }
+    if let Some(&numbers) = context.table.borrow().get(&fingerprint) {
+        return numbers;
+    }
+    if response.is_terminal() {
+        return result_numbers(context.re, &**response, context.goal);
+    }
+    (1, 1)
+}
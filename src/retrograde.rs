@@ -0,0 +1,405 @@
+//! Retrograde Analysis
+//!
+//! This module generates endgame tablebases by retrograde analysis. Every state of a bounded state space is enumerated, the
+//! results of the states in which the game is over are determined, and the results are propagated backwards to the states
+//! that precede them, until the result of every state with perfect play is known. The game-specific components are provided
+//! by the user using the [`StateSpace`] trait defined here, the [`ResponseGenerator`] trait and the [`ResultEvaluator`]
+//! trait.
+//!
+//! # Results
+//! The result of each state is stored with its distance to the end of the game in plies. The winning player is assumed to
+//! win as quickly as possible and the losing player is assumed to lose as slowly as possible. A state from which neither
+//! player can force a win is a draw, and its distance is 0.
+//!
+//! # Tablebase Format
+//! A [`Tablebase`] is saved as a header followed by one 16-bit little-endian entry per index. The header is the bytes
+//! `GPTB`, a 32-bit format version and the 64-bit number of entries. Each entry holds the result in its top 2 bits and the
+//! distance in the remaining 14 bits.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use game_player::retrograde::Tablebase;
+//!
+//! // Assuming you have implemented the required traits for your endgame
+//! let tablebase = Tablebase::generate(&endgame_space, &result_evaluator, &response_generator);
+//! tablebase.save("endgame.tb")?;
+//!
+//! // Later...
+//! let tablebase = Tablebase::load("endgame.tb")?;
+//! if let Some(solution) = tablebase.probe_state(&endgame_space, &game_state) {
+//!     println!("{:?} in {} plies", solution.result, solution.distance);
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::minimax::ResponseGenerator;
+use crate::pns::{GameResult, ResultEvaluator};
+use crate::state::*;
+use crate::static_evaluator::Oracle;
+
+/// An interface for enumerating a bounded state space.
+///
+/// Every state in the space is identified by an index from 0 to `size() - 1`. Indexes do not need to be dense; an index that
+/// does not correspond to a valid state is skipped. Every response to a state in the space must also be in the space.
+///
+/// # Examples
+/// ```rust
+/// # use game_player::State;
+/// # use game_player::retrograde::StateSpace;
+/// # #[derive(Clone)]
+/// # struct Nim { stones: u32, player: u8 }
+/// # impl State for Nim {
+/// #     type Action = u32;
+/// #     fn fingerprint(&self) -> u64 { (self.stones * 2 + self.player as u32) as u64 }
+/// #     fn whose_turn(&self) -> u8 { self.player }
+/// #     fn is_terminal(&self) -> bool { self.stones == 0 }
+/// #     fn apply(&self, taken: &u32) -> Self { Nim { stones: self.stones - taken, player: 1 - self.player } }
+/// # }
+/// // Games of Nim with up to 10 stones, in which a player takes 1 to 3 stones
+/// struct NimSpace;
+///
+/// impl StateSpace for NimSpace {
+///     type State = Nim;
+///
+///     fn size(&self) -> usize {
+///         11 * 2
+///     }
+///
+///     fn index(&self, state: &Nim) -> usize {
+///         state.stones as usize * 2 + state.player as usize
+///     }
+///
+///     fn state(&self, index: usize) -> Option<Nim> {
+///         Some(Nim { stones: (index / 2) as u32, player: (index % 2) as u8 })
+///     }
+///
+///     fn predecessors(&self, state: &Nim) -> Vec<Nim> {
+///         (state.stones + 1..=(state.stones + 3).min(10)).map(|stones| Nim { stones, player: 1 - state.player }).collect()
+///     }
+/// }
+///
+/// assert_eq!(NimSpace.index(&NimSpace.state(7).unwrap()), 7);
+/// ```
+pub trait StateSpace {
+    /// The type of the states in the space
+    type State: State;
+
+    /// Returns the number of indexes in the space.
+    fn size(&self) -> usize;
+
+    /// Returns the index of a state.
+    ///
+    /// # Arguments
+    /// * `state` - A state in the space
+    ///
+    /// # Returns
+    /// The index of the state, which is less than `size()`
+    fn index(&self, state: &Self::State) -> usize;
+
+    /// Returns the state with the given index.
+    ///
+    /// # Arguments
+    /// * `index` - An index less than `size()`
+    ///
+    /// # Returns
+    /// The state, or `None` if the index does not correspond to a valid state
+    fn state(&self, index: usize) -> Option<Self::State>;
+
+    /// Returns the states that have the given state as a response.
+    ///
+    /// # Arguments
+    /// * `state` - A state in the space
+    ///
+    /// # Returns
+    /// Every state in the space that has a response equal to `state`. Duplicates are ignored.
+    fn predecessors(&self, state: &Self::State) -> Vec<Self::State>;
+
+    /// Returns true if the state is in the space.
+    ///
+    /// This is used when probing a tablebase with states that may not be in the space, such as a state of the full game
+    /// that is not one of the endgames in the tablebase (see [`TablebaseOracle`]).
+    ///
+    /// # Returns
+    /// True if the state is in the space. The default is true.
+    fn contains(&self, _state: &Self::State) -> bool {
+        true
+    }
+}
+
+/// The result of a state with perfect play, and the number of plies to the end of the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Solution {
+    /// The result of the game
+    pub result: GameResult,
+    /// The number of plies until the end of the game, or 0 if the result is a draw
+    pub distance: u16,
+}
+
+/// An endgame tablebase, holding the solution of every state in a state space.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tablebase {
+    entries: Vec<u16>,
+}
+
+const MAGIC: &[u8; 4] = b"GPTB";
+const VERSION: u32 = 1;
+
+// Entry layout. An entry of 0 is an invalid index.
+const RESULT_SHIFT: u32 = 14;
+const DISTANCE_MASK: u16 = (1 << RESULT_SHIFT) - 1;
+const INVALID: u16 = 0;
+
+impl Tablebase {
+    /// The maximum distance that can be stored
+    pub const MAX_DISTANCE: u16 = DISTANCE_MASK;
+
+    /// Generates a tablebase by retrograde analysis.
+    ///
+    /// # Arguments
+    /// * `space` - The state space
+    /// * `re` - The result evaluator, which gives the result of the states that are terminal or have no responses
+    /// * `rg` - The response generator. It is called with a depth of 0.
+    ///
+    /// # Returns
+    /// The tablebase
+    ///
+    /// # Panics
+    /// Panics if a distance exceeds `Tablebase::MAX_DISTANCE`, or if a response or predecessor is not in the space.
+    pub fn generate<SS, E, R>(space: &SS, re: &E, rg: &R) -> Self
+    where
+        SS: StateSpace,
+        E: ResultEvaluator<SS::State>,
+        R: ResponseGenerator<State = SS::State>,
+    {
+        let size = space.size();
+        let mut entries = vec![INVALID; size];
+
+        // The number of responses of each state that have not been found to lose for the player to move. A state is solved
+        // once this is 0.
+        let mut remaining = vec![0u32; size];
+
+        // Solved states whose predecessors have not been updated, in order of distance
+        let mut queue = VecDeque::new();
+
+        for (index, entry) in entries.iter_mut().enumerate() {
+            let Some(state) = space.state(index) else {
+                continue;
+            };
+            let state = Rc::new(state);
+            let responses = if state.is_terminal() { Vec::new() } else { rg.generate(&state, 0) };
+            if responses.is_empty() {
+                let result = re.result(&*state);
+                *entry = encode(result, 0);
+                if result != GameResult::Draw {
+                    queue.push_back(index);
+                }
+            } else {
+                // Until it is solved, the state is a draw
+                *entry = encode(GameResult::Draw, 0);
+                remaining[index] = distinct_indexes(space, responses.iter().map(|response| &**response)).len() as u32;
+            }
+        }
+
+        while let Some(index) = queue.pop_front() {
+            let Solution { result, distance } = decode(entries[index]).unwrap();
+            let state = space.state(index).unwrap();
+            let predecessors = space.predecessors(&state);
+            for predecessor in distinct_indexes(space, predecessors.iter()) {
+                if remaining[predecessor] == 0 {
+                    continue;
+                }
+
+                // The player to move wins with the first winning response found, since it is the quickest. The player to move
+                // loses once every response has been found to lose, and the last one found is the slowest.
+                let player = space.state(predecessor).unwrap().whose_turn();
+                if result.winner().is_some_and(|winner| winner as u8 == player) {
+                    remaining[predecessor] = 0;
+                } else {
+                    remaining[predecessor] -= 1;
+                }
+                if remaining[predecessor] == 0 {
+                    assert!(distance < Self::MAX_DISTANCE, "The distance exceeds the maximum of {}", Self::MAX_DISTANCE);
+                    entries[predecessor] = encode(result, distance + 1);
+                    queue.push_back(predecessor);
+                }
+            }
+        }
+
+        Self { entries }
+    }
+
+    /// Returns the number of entries, which is the size of the state space.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the tablebase has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the solution of the state with the given index.
+    ///
+    /// # Arguments
+    /// * `index` - The index of the state
+    ///
+    /// # Returns
+    /// The solution, or `None` if the index is out of range or does not correspond to a valid state
+    pub fn probe(&self, index: usize) -> Option<Solution> {
+        self.entries.get(index).copied().and_then(decode)
+    }
+
+    /// Returns the solution of a state.
+    ///
+    /// # Arguments
+    /// * `space` - The state space used to generate the tablebase
+    /// * `state` - The state
+    ///
+    /// # Returns
+    /// The solution, or `None` if the state is not in the tablebase
+    pub fn probe_state<SS: StateSpace>(&self, space: &SS, state: &SS::State) -> Option<Solution> {
+        self.probe(space.index(state))
+    }
+
+    /// Saves the tablebase to a file.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be created or written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Loads a tablebase from a file.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not a tablebase of a supported version.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Writes the tablebase in the tablebase format.
+    ///
+    /// # Errors
+    /// Returns an error if the writer fails.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(self.entries.len() as u64).to_le_bytes())?;
+        for entry in &self.entries {
+            writer.write_all(&entry.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Reads a tablebase in the tablebase format.
+    ///
+    /// # Errors
+    /// Returns an error if the reader fails or the data is not a tablebase of a supported version.
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a tablebase"));
+        }
+        let mut version = [0; 4];
+        reader.read_exact(&mut version)?;
+        if u32::from_le_bytes(version) != VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "unsupported tablebase version"));
+        }
+        let mut count = [0; 8];
+        reader.read_exact(&mut count)?;
+        let length = u64::from_le_bytes(count)
+            .checked_mul(2)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "tablebase is too large"))?;
+
+        let mut bytes = Vec::new();
+        reader.take(length).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != length {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "tablebase is truncated"));
+        }
+        let entries = bytes.chunks_exact(2).map(|entry| u16::from_le_bytes([entry[0], entry[1]])).collect();
+        Ok(Self { entries })
+    }
+}
+
+/// An [`Oracle`] that probes a tablebase, so that the minimax search can use it.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use game_player::retrograde::{Tablebase, TablebaseOracle};
+///
+/// struct MyEvaluator {
+///     oracle: TablebaseOracle<EndgameSpace>,
+/// }
+///
+/// impl StaticEvaluator<MyGameState> for MyEvaluator {
+///     // ...
+///     fn oracle(&self) -> Option<&dyn Oracle<MyGameState>> {
+///         Some(&self.oracle)
+///     }
+/// }
+///
+/// let evaluator = MyEvaluator { oracle: TablebaseOracle::new(EndgameSpace, Tablebase::load("endgame.tb")?) };
+/// ```
+pub struct TablebaseOracle<SS> {
+    space: SS,
+    tablebase: Tablebase,
+}
+
+impl<SS: StateSpace> TablebaseOracle<SS> {
+    /// Creates an oracle from a tablebase and the state space used to generate it.
+    pub fn new(space: SS, tablebase: Tablebase) -> Self {
+        Self { space, tablebase }
+    }
+}
+
+impl<SS: StateSpace> Oracle<SS::State> for TablebaseOracle<SS> {
+    fn probe(&self, state: &SS::State) -> Option<Solution> {
+        if !self.space.contains(state) {
+            return None;
+        }
+        self.tablebase.probe_state(&self.space, state)
+    }
+}
+
+// Returns the sorted indexes of the states, without duplicates
+fn distinct_indexes<'a, SS: StateSpace>(space: &SS, states: impl Iterator<Item = &'a SS::State>) -> Vec<usize>
+where
+    SS::State: 'a,
+{
+    let mut indexes: Vec<usize> = states.map(|state| space.index(state)).collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+    indexes
+}
+
+fn encode(result: GameResult, distance: u16) -> u16 {
+    let code = match result {
+        GameResult::AliceWins => 1,
+        GameResult::BobWins => 2,
+        GameResult::Draw => 3,
+    };
+    code << RESULT_SHIFT | distance
+}
+
+fn decode(entry: u16) -> Option<Solution> {
+    let result = match entry >> RESULT_SHIFT {
+        1 => GameResult::AliceWins,
+        2 => GameResult::BobWins,
+        3 => GameResult::Draw,
+        _ => return None,
+    };
+    Some(Solution {
+        result,
+        distance: entry & DISTANCE_MASK,
+    })
+}
@@ -0,0 +1,212 @@
+//! Score Types
+//!
+//! This module defines the `Score` trait, which is implemented by the types that can be used as the value of a game state.
+
+use std::cmp::Ordering;
+use std::fmt::Debug;
+
+/// A type that can be used as the value of a game state.
+///
+/// Values are compared with a total order, so the search behaves consistently even if a floating point evaluator returns a
+/// NaN. Integer scores are compared exactly and need less space in the transposition table.
+///
+/// Implementations are provided for `i16`, `i32`, and `f32`.
+///
+/// # Examples
+/// ```rust
+/// use std::cmp::Ordering;
+/// use game_player::score::Score;
+///
+/// assert_eq!(3i16.total_cmp(&5), Ordering::Less);
+/// assert_eq!(f32::NAN.total_cmp(&f32::INFINITY), Ordering::Greater);
+/// assert_eq!(<i32 as Score>::from_f32(2.6), 3);
+/// ```
+pub trait Score: Copy + PartialEq + PartialOrd + Debug + Default + 'static {
+    /// The lowest possible value. It is used as the initial bound of a search, so an evaluator should not return it.
+    const MIN: Self;
+
+    /// The highest possible value. It is used as the initial bound of a search, so an evaluator should not return it.
+    const MAX: Self;
+
+    /// Compares two values using a total order.
+    ///
+    /// For floating point values, this is the IEEE 754 `totalOrder` predicate (see [`f32::total_cmp`]).
+    fn total_cmp(&self, other: &Self) -> Ordering;
+
+    /// Converts a floating point value to a value of this type, rounding and saturating if necessary.
+    fn from_f32(value: f32) -> Self;
+
+    /// Converts this value to a floating point value.
+    fn to_f32(self) -> f32;
+
+    /// Adds two values, saturating at the bounds of the type instead of overflowing.
+    ///
+    /// The default adds the values converted to `f32`.
+    fn saturating_add(self, other: Self) -> Self {
+        Self::from_f32(self.to_f32() + other.to_f32())
+    }
+
+    /// Subtracts a value from this value, saturating at the bounds of the type instead of overflowing.
+    ///
+    /// The default subtracts the values converted to `f32`.
+    fn saturating_sub(self, other: Self) -> Self {
+        Self::from_f32(self.to_f32() - other.to_f32())
+    }
+
+    /// Converts this value to 32 bits for storage (see [`from_bits`](Self::from_bits)).
+    ///
+    /// The default stores the value converted to `f32`, which is exact for `f32`.
+    fn to_bits(self) -> u32 {
+        self.to_f32().to_bits()
+    }
+
+    /// Converts 32 bits returned by [`to_bits`](Self::to_bits) back to a value.
+    fn from_bits(bits: u32) -> Self {
+        Self::from_f32(f32::from_bits(bits))
+    }
+
+    /// Converts this value to a JSON value.
+    ///
+    /// The default converts the value to `f32`, which is exact for `f32`.
+    #[cfg(feature = "serde_json")]
+    fn to_json(self) -> serde_json::Value {
+        serde_json::Value::from(self.to_f32())
+    }
+}
+
+impl Score for f32 {
+    const MIN: Self = f32::NEG_INFINITY;
+    const MAX: Self = f32::INFINITY;
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f32::total_cmp(self, other)
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    fn saturating_add(self, other: Self) -> Self {
+        self + other
+    }
+
+    fn saturating_sub(self, other: Self) -> Self {
+        self - other
+    }
+}
+
+impl Score for i16 {
+    const MIN: Self = i16::MIN;
+    const MAX: Self = i16::MAX;
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value.round() as i16
+    }
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    fn saturating_add(self, other: Self) -> Self {
+        i16::saturating_add(self, other)
+    }
+
+    fn saturating_sub(self, other: Self) -> Self {
+        i16::saturating_sub(self, other)
+    }
+
+    fn to_bits(self) -> u32 {
+        self as u16 as u32
+    }
+
+    fn from_bits(bits: u32) -> Self {
+        bits as u16 as i16
+    }
+
+    #[cfg(feature = "serde_json")]
+    fn to_json(self) -> serde_json::Value {
+        serde_json::Value::from(self)
+    }
+}
+
+impl Score for i32 {
+    const MIN: Self = i32::MIN;
+    const MAX: Self = i32::MAX;
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value.round() as i32
+    }
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    fn saturating_add(self, other: Self) -> Self {
+        i32::saturating_add(self, other)
+    }
+
+    fn saturating_sub(self, other: Self) -> Self {
+        i32::saturating_sub(self, other)
+    }
+
+    fn to_bits(self) -> u32 {
+        self as u32
+    }
+
+    fn from_bits(bits: u32) -> Self {
+        bits as i32
+    }
+
+    #[cfg(feature = "serde_json")]
+    fn to_json(self) -> serde_json::Value {
+        serde_json::Value::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_f32_total_order() {
+        assert_eq!(Score::total_cmp(&1.0f32, &2.0), Ordering::Less);
+        assert_eq!(Score::total_cmp(&f32::NAN, &<f32 as Score>::MAX), Ordering::Greater);
+        assert_eq!(Score::total_cmp(&<f32 as Score>::MIN, &f32::MIN), Ordering::Less);
+    }
+
+    #[test]
+    fn test_integer_conversions() {
+        assert_eq!(<i16 as Score>::from_f32(-2.5), -3);
+        assert_eq!(<i16 as Score>::from_f32(1.0e9), i16::MAX);
+        assert_eq!(<i32 as Score>::from_f32(0.4), 0);
+        assert_eq!(Score::to_f32(-7i16), -7.0);
+        assert_eq!(Score::to_f32(100_000i32), 100_000.0);
+    }
+
+    #[test]
+    fn test_saturating_arithmetic() {
+        assert_eq!(Score::saturating_add(16_777_217i32, 2), 16_777_219);
+        assert_eq!(Score::saturating_sub(i32::MIN + 1, 5), i32::MIN);
+        assert_eq!(Score::saturating_add(i16::MAX, 1), i16::MAX);
+        assert_eq!(Score::saturating_sub(<f32 as Score>::MAX, 1.0), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_bits_round_trip() {
+        assert_eq!(<f32 as Score>::from_bits(Score::to_bits(-1.5f32)), -1.5);
+        assert_eq!(<i16 as Score>::from_bits(Score::to_bits(i16::MIN)), i16::MIN);
+        assert_eq!(<i32 as Score>::from_bits(Score::to_bits(-123_456_789i32)), -123_456_789);
+    }
+}
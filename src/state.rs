@@ -6,7 +6,8 @@
 /// IDs of the players in a two-player game.
 ///
 /// This enumeration defines the two possible players. The numeric values (0 and 1) can be used for array indexing and other
-/// performance-critical operations. These are provided for convenience and are not required to be used.
+/// performance-critical operations. These are provided for convenience and are not required to be used. Games with more than
+/// two players identify players by index instead (see [`multiplayer`](crate::multiplayer)).
 ///
 /// # Examples
 ///
@@ -198,6 +199,49 @@ pub trait State: Sized {
     /// ```
     fn is_terminal(&self) -> bool;
 
+    /// Checks if the game has ended in a draw.
+    ///
+    /// This is only meaningful for terminal states. A state reporting a draw is given the draw value (adjusted by contempt)
+    /// by the search instead of being evaluated by the static evaluator.
+    ///
+    /// # Returns
+    /// `true` if the game is over and neither player has won, `false` otherwise. The default is `false`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use game_player::{State, PlayerId};
+    /// # #[derive(Clone, Default)]
+    /// # struct MyAction;
+    /// # #[derive(Clone, Copy)]
+    /// # struct MyGameState { game_is_over: bool, winner: Option<PlayerId> }
+    /// # impl State for MyGameState {
+    /// #     type Action = MyAction;
+    /// #     fn fingerprint(&self) -> u64 { 42 }
+    /// #     fn whose_turn(&self) -> u8 { 0 }
+    /// #     fn is_terminal(&self) -> bool { self.game_is_over }
+    /// #     fn is_draw(&self) -> bool { self.game_is_over && self.winner.is_none() }
+    /// #     fn apply(&self, _action: &Self::Action) -> Self { *self }
+    /// # }
+    /// let state = MyGameState { game_is_over: true, winner: None };
+    /// assert!(state.is_draw());
+    /// ```
+    fn is_draw(&self) -> bool {
+        false
+    }
+
+    /// Returns a second hash of this state, used to verify transposition table entries.
+    ///
+    /// If the transposition table has verification keys (see
+    /// [`TranspositionTable::with_verification`](crate::transposition_table::TranspositionTable::with_verification)), an entry
+    /// is found only if both its fingerprint and its key match, so two states with the same fingerprint are much less likely to
+    /// be confused. The key should be independent of the fingerprint.
+    ///
+    /// # Returns
+    /// The verification key. The default is 0, so states are identified by their fingerprints alone.
+    fn verification_key(&self) -> u64 {
+        0
+    }
+
     /// Applies an action to the current state, returning a new state as a result of the action.
     ///
     /// This method creates a new state by applying the given action to the current state.
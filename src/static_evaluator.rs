@@ -2,6 +2,9 @@
 //!
 //! This module defines the `StaticEvaluator` trait, which provides an interface for static evaluation functions.
 
+use crate::retrograde::Solution;
+use crate::score::Score;
+
 /// An interface for static evaluation functions.
 ///
 /// A static evaluation function assigns a value to a game state without any lookahead. The value represents the
@@ -9,8 +12,36 @@
 ///
 /// The values returned by the static evaluation function should be in the range [bobWinsValue(), aliceWinsValue()].
 /// If the game is over and Alice has won, then the function should return aliceWinsValue(). If the game is over and
-/// Bob has won, then the function should return bobWinsValue().
-pub trait StaticEvaluator<G> {
+/// Bob has won, then the function should return bobWinsValue(). A terminal state that reports itself as a draw (see
+/// [`State::is_draw`](crate::State::is_draw)) is not evaluated; the search uses drawValue() instead.
+///
+/// The type of the values is `V`, which is `f32` by default. A game with integer values can use `i16` or `i32` instead (see
+/// [`Score`]).
+///
+/// # Examples
+/// ```rust
+/// # use game_player::StaticEvaluator;
+/// struct Material;
+///
+/// // A state is represented here by the material of each player
+/// impl StaticEvaluator<(i16, i16), i16> for Material {
+///     fn evaluate(&self, state: &(i16, i16)) -> i16 {
+///         state.0 - state.1
+///     }
+///
+///     fn alice_wins_value(&self) -> i16 {
+///         10000
+///     }
+///
+///     fn bob_wins_value(&self) -> i16 {
+///         -10000
+///     }
+/// }
+///
+/// assert_eq!(Material.evaluate(&(39, 36)), 3);
+/// assert_eq!(Material.draw_value(), 0);
+/// ```
+pub trait StaticEvaluator<G, V: Score = f32> {
     /// Evaluates the given state and returns its value from Alice's perspective.
     ///
     /// # Arguments
@@ -21,7 +52,7 @@ pub trait StaticEvaluator<G> {
     ///
     /// # Note
     /// This function must be implemented.
-    fn evaluate(&self, state: &G) -> f32;
+    fn evaluate(&self, state: &G) -> V;
 
     /// Returns the value that indicates that Alice has won.
     ///
@@ -30,7 +61,7 @@ pub trait StaticEvaluator<G> {
     ///
     /// # Note
     /// This function must be implemented.
-    fn alice_wins_value(&self) -> f32;
+    fn alice_wins_value(&self) -> V;
 
     /// Returns the value that indicates that Bob has won.
     ///
@@ -39,5 +70,73 @@ pub trait StaticEvaluator<G> {
     ///
     /// # Note
     /// This function must be implemented.
-    fn bob_wins_value(&self) -> f32;
+    fn bob_wins_value(&self) -> V;
+
+    /// Returns the value of a drawn game.
+    ///
+    /// # Returns
+    /// The value of a draw from Alice's perspective. The default is 0.
+    ///
+    /// # Note
+    /// The search adjusts this value by the contempt factor in effect, from the perspective of the player to move at the root.
+    fn draw_value(&self) -> V {
+        V::default()
+    }
+
+    /// Returns the value of a state that repeats a previous state.
+    ///
+    /// The search calls this for a state that repeats an earlier state in the game or on the search path, instead of
+    /// evaluating it. Games in which a repetition is not simply a draw (e.g. a repetition is a loss for the player causing
+    /// it) can override this.
+    ///
+    /// # Arguments
+    /// * `state` - The repeated state
+    ///
+    /// # Returns
+    /// The value of the repeated state from Alice's perspective, or `None` to treat the repetition as a draw. The default
+    /// is `None`.
+    fn repetition_value(&self, _state: &G) -> Option<V> {
+        None
+    }
+
+    /// Returns the oracle that the search consults for exact results, such as an endgame tablebase.
+    ///
+    /// The search probes the oracle for every state before using the transposition table or evaluating the state. A state
+    /// whose result is known is not evaluated or searched further.
+    ///
+    /// # Returns
+    /// The oracle, or `None` if there is none. The default is `None`.
+    fn oracle(&self) -> Option<&dyn Oracle<G>> {
+        None
+    }
+}
+
+/// An interface for a source of exact results, such as an endgame tablebase.
+///
+/// # Examples
+/// ```rust
+/// # use game_player::pns::GameResult;
+/// # use game_player::retrograde::Solution;
+/// # use game_player::static_evaluator::Oracle;
+/// struct KnownWins;
+///
+/// // A state is represented here by the number of moves Alice needs to win, if known
+/// impl Oracle<Option<u16>> for KnownWins {
+///     fn probe(&self, state: &Option<u16>) -> Option<Solution> {
+///         state.map(|moves| Solution { result: GameResult::AliceWins, distance: moves * 2 - 1 })
+///     }
+/// }
+///
+/// assert_eq!(KnownWins.probe(&Some(2)).unwrap().distance, 3);
+/// assert_eq!(KnownWins.probe(&None), None);
+/// ```
+pub trait Oracle<G> {
+    /// Returns the result of the given state with perfect play.
+    ///
+    /// # Arguments
+    /// * `state` - The state
+    ///
+    /// # Returns
+    /// The result and the number of plies to the end of the game, or `None` if the result of the state is not known
+    fn probe(&self, state: &G) -> Option<Solution>;
 }
@@ -1,4 +1,101 @@
 //! Transposition Table
+//!
+//! # Table Format
+//! A table is saved (see [`TranspositionTable::save`]) as a header followed by the entries that are in use. The header is the
+//! bytes `GPTT`, a 32-bit format version, the 64-bit number of entries in the table, the 32-bit number of ways, the 16-bit
+//! maximum age, 16 bits of flags, the 64-bit number of saved entries, and a 64-bit FNV-1a checksum of the rest of the header
+//! and the saved entries. Each saved entry is its 64-bit fingerprint, its value as 32 bits (see [`Score::to_bits`]), its
+//! 16-bit quality, its 16-bit age, the 16-bit index of its entry in its bucket and, if flag bit 0 is set (the table has
+//! verification keys), its 64-bit verification key. All values are little-endian.
+
+use std::cmp::Reverse;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::mem::size_of;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::score::Score;
+
+#[cfg(feature = "analysis_transposition_table")]
+pub mod analysis;
+pub mod replacement;
+
+use replacement::{DepthPreferred, ReplacementPolicy, Slot};
+
+/// Transposition table statistics.
+///
+/// The statistics are accumulated until they are reset with [`TranspositionTable::reset_statistics`]. They are available only
+/// with the `transposition_table_statistics` feature.
+#[cfg(feature = "transposition_table_statistics")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Statistics {
+    /// The number of calls to [`check`](TranspositionTable::check)
+    pub probes: u64,
+    /// The number of probes that found a value of sufficient quality
+    pub hits: u64,
+    /// The number of probes that did not find a value, or found a value of insufficient quality
+    pub misses: u64,
+    /// The number of values that were not stored because the replacement policy rejected them (e.g. their quality was too low)
+    pub rejected: u64,
+    /// The number of entries of other states that were overwritten by new entries
+    pub collisions: u64,
+    /// The number of entries that were removed because they expired
+    pub evictions: u64,
+}
+
+/// The maximum number of entries in a bucket
+pub const MAX_WAYS: usize = 16;
+
+/// An error returned by the fallible (`try_`) methods of [`TranspositionTable`] instead of panicking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableError {
+    /// The number of entries is 0
+    ZeroSize,
+    /// The number of ways is 0 or more than [`MAX_WAYS`]
+    InvalidWays(usize),
+    /// The maximum age is 0 or negative
+    InvalidMaxAge(i16),
+    /// The memory budget is less than the size of one bucket
+    InsufficientMemory {
+        /// The memory budget, in bytes
+        bytes: usize,
+        /// The size of one bucket, in bytes
+        bucket: usize,
+    },
+    /// The quality of a value is negative
+    NegativeQuality(i16),
+    /// The memory for the given number of entries cannot be allocated
+    TooLarge(usize),
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroSize => write!(f, "the size of a transposition table must be at least 1"),
+            Self::InvalidWays(ways) => write!(f, "the number of ways must be from 1 to {MAX_WAYS}, not {ways}"),
+            Self::InvalidMaxAge(max_age) => write!(f, "the maximum age must be at least 1, not {max_age}"),
+            Self::InsufficientMemory { bytes, bucket } => {
+                write!(f, "the memory budget of {bytes} bytes is less than one bucket of {bucket} bytes")
+            }
+            Self::NegativeQuality(quality) => write!(f, "the quality must not be negative, not {quality}"),
+            Self::TooLarge(size) => write!(f, "a transposition table of {size} entries is too large to allocate"),
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
+
+const MAGIC: &[u8; 4] = b"GPTT";
+const VERSION: u32 = 3;
+const HEADER_SIZE: usize = 32; // Not including the checksum
+const RECORD_SIZE: usize = 18; // Not including the verification key
+const FLAG_VERIFICATION: u16 = 1;
+
+// The number of generations between sweeps of the table. Entries that are this old are removed by a sweep, so the age of an
+// entry never exceeds 2 * SWEEP_INTERVAL - 1, which fits in the 16-bit generation stamp.
+const SWEEP_INTERVAL: u16 = 0x8000;
 
 /// A map of game state values referenced by the states' fingerprints.
 ///
@@ -11,8 +108,29 @@
 /// value may be overwritten when a new value is added. A value is overwritten only when its "quality" is less than or equal to the
 /// "quality" of the value being added.
 ///
+/// By default, each fingerprint maps to a single slot (the table is direct-mapped). A table created with
+/// [`with_ways`](Self::with_ways) is set-associative instead: each fingerprint maps to a bucket of several slots, and when the
+/// bucket is full, the entry with the lowest quality (and then the oldest) is the one that may be replaced.
+///
+/// The rules above are those of the default [`DepthPreferred`] replacement policy. A different policy can be chosen with
+/// [`with_policy`](Self::with_policy) (see [`replacement`]).
+///
+/// The type of the stored values is `T`, which is `f32` by default, making each entry 16 bytes. Any [`Score`] type can be
+/// used: an integer type results in exact values and, in the case of `i16`, smaller entries. A table can also cache other
+/// per-state data with the same fingerprint, quality and age machinery (e.g. a best move, a bound type or search statistics)
+/// by storing any `Copy + Default` type, at the cost of larger entries. Saving and loading a table (and exporting it as
+/// JSON) requires a [`Score`] type.
+///
+/// An entry is found by its fingerprint alone, so two states with the same fingerprint share an entry. For very long runs,
+/// a table created with [`with_verification`](Self::with_verification) also stores a second 64-bit key with each entry (see
+/// [`State::verification_key`](crate::State::verification_key)), and an entry is found only if both match.
+///
+/// The constructors and the methods that store values panic if their arguments are invalid. Each has a fallible `try_`
+/// variant (e.g. [`try_new`](Self::try_new) and [`try_update`](Self::try_update)) that returns a [`TableError`] instead.
+/// [`check`](Self::check) never panics.
+///
 /// # Note
-/// The fingerprint is assumed to be a random and uniformly distributed 64-bit value. It is assumed to never be u64::MAX.
+/// The fingerprint is assumed to be a random and uniformly distributed 64-bit value. Any value is valid.
 ///
 /// # Examples
 ///
@@ -28,12 +146,34 @@
 ///     assert_eq!(value, 0.75);
 ///     assert_eq!(quality, 5);
 /// }
+///
+/// // A table of other data
+/// #[derive(Clone, Copy, Default, Debug, PartialEq)]
+/// struct Info {
+///     best_move: u16,
+///     lower_bound: bool,
+/// }
+///
+/// let mut table = TranspositionTable::new(1000, 100);
+/// table.update(12345, Info { best_move: 7, lower_bound: true }, 5);
+/// assert_eq!(table.check(12345, -1), Some((Info { best_move: 7, lower_bound: true }, 5)));
 /// ```
-pub struct TranspositionTable {
+pub struct TranspositionTable<T = f32> {
     /// The table of entries
-    table: Vec<Entry>,
+    table: Vec<Entry<T>>,
+    /// The verification keys of the entries, or empty if the table does not verify keys
+    keys: Vec<u64>,
     /// The maximum age of entries allowed in the table
     max_age: i16,
+    /// The number of entries in each bucket
+    ways: usize,
+    /// The replacement policy
+    policy: Box<dyn ReplacementPolicy>,
+    /// The current generation, which is incremented each time the table is aged
+    generation: u16,
+    /// Statistics
+    #[cfg(feature = "transposition_table_statistics")]
+    statistics: Statistics,
 }
 
 // Table entry
@@ -42,39 +182,101 @@ pub struct TranspositionTable {
 // entry should replace an existing one. Now, an entry that has not been referenced for a while will probably never be
 // referenced again, so it should eventually be allowed to be replaced by a newer entry, regardless of the quality of the new
 // entry.
-#[derive(Clone)]
-#[repr(C, packed)] // 16 bytes
-struct Entry {
+//
+// Rather than storing an age that must be incremented in every entry, each entry is stamped with the generation of the table
+// when it is referenced. Its age is the number of generations since then.
+//
+// An unused entry has a negative quality, since the quality of a stored value is never negative. Thus, every fingerprint is
+// valid.
+#[derive(Clone, Copy)]
+#[repr(C, packed)] // 16 bytes for f32 values
+struct Entry<T> {
     fingerprint: u64, // The state's fingerprint
-    value: f32,       // The state's value
-    q: i16,           // The quality of the value
-    age: i16,         // The number of turns since the entry has been referenced
+    value: T,         // The state's value
+    q: i16,           // The quality of the value, or -1 if the entry is unused
+    generation: u16,  // The generation of the table when the entry was last referenced
 }
 
-impl Entry {
-    const UNUSED: u64 = u64::MAX;
+impl<T> Entry<T> {
+    fn is_unused(&self) -> bool {
+        self.q < 0
+    }
 
     fn clear(&mut self) {
-        self.fingerprint = Self::UNUSED;
+        self.q = -1;
+    }
+
+    // Returns the age of the entry in the given generation of the table
+    fn age(&self, generation: u16) -> u16 {
+        generation.wrapping_sub(self.generation)
+    }
+
+    fn slot(&self, generation: u16) -> Slot {
+        Slot {
+            quality: self.q,
+            age: self.age(generation).min(i16::MAX as u16) as i16,
+        }
     }
 }
 
-impl Default for Entry {
+impl<T: Score> Entry<T> {
+    // Returns the entry as it is saved in the table format, given its index in its bucket
+    fn record(&self, generation: u16, way: usize) -> [u8; RECORD_SIZE] {
+        let mut record = [0; RECORD_SIZE];
+        record[0..8].copy_from_slice(&{ self.fingerprint }.to_le_bytes());
+        record[8..12].copy_from_slice(&self.value.to_bits().to_le_bytes());
+        record[12..14].copy_from_slice(&{ self.q }.to_le_bytes());
+        record[14..16].copy_from_slice(&self.age(generation).to_le_bytes());
+        record[16..18].copy_from_slice(&(way as u16).to_le_bytes());
+        record
+    }
+
+    // Returns an entry read from the table format and its index in its bucket, or None if it is not valid
+    fn from_record(record: &[u8; RECORD_SIZE], generation: u16) -> Option<(Self, usize)> {
+        let fingerprint = u64::from_le_bytes(record[0..8].try_into().unwrap());
+        let value = T::from_bits(u32::from_le_bytes(record[8..12].try_into().unwrap()));
+        let q = i16::from_le_bytes(record[12..14].try_into().unwrap());
+        let age = u16::from_le_bytes(record[14..16].try_into().unwrap());
+        let way = u16::from_le_bytes(record[16..18].try_into().unwrap()) as usize;
+        if q < 0 || age > i16::MAX as u16 {
+            return None;
+        }
+        let entry = Self {
+            fingerprint,
+            value,
+            q,
+            generation: generation.wrapping_sub(age),
+        };
+        Some((entry, way))
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// Continues an FNV-1a hash with more bytes
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+impl<T: Default> Default for Entry<T> {
     fn default() -> Self {
         Self {
-            fingerprint: Self::UNUSED,
-            value: 0.0,
-            q: 0,
-            age: 0,
+            fingerprint: 0,
+            value: T::default(),
+            q: -1,
+            generation: 0,
         }
     }
 }
 
 // Check that the size of Entry is 16 bytes. The size is not required to be 16 bytes, but 16 bytes is an optimal size.
 static_assertions::assert_eq_size!(f32, [u8; 4]); // float should be 32 bits
-static_assertions::assert_eq_size!(Entry, [u8; 16]); // Entry should be 16 bytes
+static_assertions::assert_eq_size!(Entry<f32>, [u8; 16]); // Entry should be 16 bytes
+static_assertions::assert_eq_size!(Entry<i32>, [u8; 16]);
+static_assertions::assert_eq_size!(Entry<i16>, [u8; 14]);
 
-impl TranspositionTable {
+impl<T: Copy + Default> TranspositionTable<T> {
     /// Creates a new TranspositionTable
     ///
     /// # Arguments
@@ -85,8 +287,11 @@ impl TranspositionTable {
     ///
     /// ```rust
     /// # use game_player::transposition_table::TranspositionTable;
-    /// let table = TranspositionTable::new(1000, 50);
+    /// let table: TranspositionTable = TranspositionTable::new(1000, 50);
     /// // Table is ready to use with 1000 entries and max age of 50
+    ///
+    /// // A table of integer values
+    /// let table = TranspositionTable::<i16>::new(1000, 50);
     /// ```
     ///
     /// # Panics
@@ -95,15 +300,310 @@ impl TranspositionTable {
     ///
     /// ```should_panic
     /// # use game_player::transposition_table::TranspositionTable;
-    /// let table = TranspositionTable::new(0, 50); // This will panic
+    /// let table: TranspositionTable = TranspositionTable::new(0, 50); // This will panic
     /// ```
     pub fn new(size: usize, max_age: i16) -> Self {
+        Self::with_ways(size, 1, max_age)
+    }
+
+    /// Creates a new TranspositionTable, or returns an error instead of panicking (see [`new`](Self::new))
+    ///
+    /// # Errors
+    /// Returns [`TableError::ZeroSize`] or [`TableError::InvalidMaxAge`] if the arguments are invalid, or
+    /// [`TableError::TooLarge`] if the table cannot be allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use game_player::transposition_table::{TableError, TranspositionTable};
+    /// assert!(TranspositionTable::<f32>::try_new(1000, 50).is_ok());
+    /// assert_eq!(TranspositionTable::<f32>::try_new(0, 50).err(), Some(TableError::ZeroSize));
+    /// ```
+    pub fn try_new(size: usize, max_age: i16) -> Result<Self, TableError> {
+        Self::try_with_ways(size, 1, max_age)
+    }
+
+    /// Creates a new set-associative TranspositionTable
+    ///
+    /// # Arguments
+    /// * `size` - Number of entries in the table. It is rounded up to a multiple of `ways`.
+    /// * `ways` - Number of entries in each bucket
+    /// * `max_age` - Maximum age of entries allowed in the table
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use game_player::transposition_table::TranspositionTable;
+    /// // 256 buckets of 4 entries
+    /// let mut table = TranspositionTable::with_ways(1024, 4, 50);
+    ///
+    /// // Both entries are kept even though they map to the same bucket
+    /// table.update(1, 1.0, 5);
+    /// table.update(257, 2.0, 3);
+    /// assert_eq!(table.check(1, -1), Some((1.0, 5)));
+    /// assert_eq!(table.check(257, -1), Some((2.0, 3)));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` or `ways` is 0, `ways` is more than [`MAX_WAYS`], or `max_age` is 0 or negative, or if the table
+    /// cannot be allocated.
+    pub fn with_ways(size: usize, ways: usize, max_age: i16) -> Self {
         assert!(size > 0);
+        assert!(ways > 0 && ways <= MAX_WAYS);
         assert!(max_age > 0);
-        Self {
-            table: vec![Entry::default(); size],
-            max_age,
+        Self::allocate(Self::round_size(size, ways), ways, max_age, false).expect("the table is too large to allocate")
+    }
+
+    /// Creates a new TranspositionTable that uses at most the given amount of memory for its entries
+    ///
+    /// The number of buckets is the largest power of two that fits in the budget, so that a fingerprint's bucket is found
+    /// with a mask rather than a division.
+    ///
+    /// # Arguments
+    /// * `bytes` - The memory budget of the table's entries, in bytes
+    /// * `ways` - Number of entries in each bucket
+    /// * `max_age` - Maximum age of entries allowed in the table
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use game_player::transposition_table::TranspositionTable;
+    /// // 256 MiB of 16-byte entries in buckets of 4
+    /// let table: TranspositionTable = TranspositionTable::with_memory(256 << 20, 4, 50);
+    /// assert_eq!(table.len(), 16 << 20);
+    /// assert_eq!(table.memory(), 256 << 20);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is less than the size of one bucket, `ways` is 0 or more than [`MAX_WAYS`], or `max_age` is 0 or
+    /// negative.
+    pub fn with_memory(bytes: usize, ways: usize, max_age: i16) -> Self {
+        Self::with_ways(Self::size_for_memory(bytes, ways), ways, max_age)
+    }
+
+    /// Creates a new set-associative TranspositionTable, or returns an error instead of panicking (see
+    /// [`with_ways`](Self::with_ways))
+    ///
+    /// # Errors
+    /// Returns [`TableError::ZeroSize`], [`TableError::InvalidWays`] or [`TableError::InvalidMaxAge`] if the arguments are
+    /// invalid, or [`TableError::TooLarge`] if the table cannot be allocated.
+    pub fn try_with_ways(size: usize, ways: usize, max_age: i16) -> Result<Self, TableError> {
+        if size == 0 {
+            return Err(TableError::ZeroSize);
+        }
+        Self::validate_ways(ways)?;
+        if max_age <= 0 {
+            return Err(TableError::InvalidMaxAge(max_age));
+        }
+        Self::allocate(Self::round_size(size, ways), ways, max_age, false)
+    }
+
+    /// Creates a new TranspositionTable that uses at most the given amount of memory, or returns an error instead of
+    /// panicking (see [`with_memory`](Self::with_memory))
+    ///
+    /// # Errors
+    /// Returns [`TableError::InsufficientMemory`], [`TableError::InvalidWays`] or [`TableError::InvalidMaxAge`] if the
+    /// arguments are invalid, or [`TableError::TooLarge`] if the table cannot be allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use game_player::transposition_table::{TableError, TranspositionTable};
+    /// let result = TranspositionTable::<f32>::try_with_memory(32, 4, 50);
+    /// assert_eq!(result.err(), Some(TableError::InsufficientMemory { bytes: 32, bucket: 64 }));
+    /// ```
+    pub fn try_with_memory(bytes: usize, ways: usize, max_age: i16) -> Result<Self, TableError> {
+        Self::validate_ways(ways)?;
+        Self::try_with_ways(Self::try_size_for_memory(bytes, ways)?, ways, max_age)
+    }
+
+    /// Enables verification keys
+    ///
+    /// A verification key is stored with each entry, and an entry is found only if both its fingerprint and its key match.
+    /// The search uses [`State::verification_key`](crate::State::verification_key) as the key. This adds 8 bytes per entry,
+    /// which are not included in a budget given to [`with_memory`](Self::with_memory). The keys are stored separately from
+    /// the entries, so a probe that finds a matching fingerprint can cost a second cache miss to check the key.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use game_player::transposition_table::TranspositionTable;
+    /// let mut table = TranspositionTable::new(100, 10).with_verification();
+    /// table.update_with_key(12345, 1, 1.0, 5);
+    /// assert_eq!(table.check_with_key(12345, 1, -1), Some((1.0, 5)));
+    ///
+    /// // Same fingerprint, different key
+    /// assert_eq!(table.check_with_key(12345, 2, -1), None);
+    /// ```
+    pub fn with_verification(mut self) -> Self {
+        self.keys = vec![0; self.table.len()];
+        self
+    }
+
+    /// Replaces the replacement policy of the table
+    ///
+    /// # Arguments
+    /// * `policy` - The new replacement policy
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use game_player::transposition_table::TranspositionTable;
+    /// use game_player::transposition_table::replacement::AlwaysReplace;
+    ///
+    /// let mut table = TranspositionTable::new(100, 10).with_policy(AlwaysReplace);
+    /// table.update(12345, 1.0, 5);
+    /// table.update(12345, 2.0, 3);
+    /// assert_eq!(table.check(12345, -1), Some((2.0, 3)));
+    /// ```
+    pub fn with_policy<P: ReplacementPolicy + 'static>(mut self, policy: P) -> Self {
+        self.policy = Box::new(policy);
+        self
+    }
+
+    /// Returns the number of entries in the table
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Returns true if the table has no entries. This is never the case, since the size of a table is at least 1.
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// Returns the amount of memory used by the table's entries, in bytes
+    pub fn memory(&self) -> usize {
+        self.table.len() * size_of::<Entry<T>>() + self.keys.len() * size_of::<u64>()
+    }
+
+    /// Returns the fraction of the table's entries that are in use (and have not expired)
+    ///
+    /// This examines every entry. See [`hashfull`](Self::hashfull) for a fast estimate.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use game_player::transposition_table::TranspositionTable;
+    /// let mut table = TranspositionTable::new(4, 10);
+    /// table.update(1, 1.0, 5);
+    /// assert_eq!(table.occupancy(), 0.25);
+    /// ```
+    pub fn occupancy(&self) -> f64 {
+        self.used(self.table.len()) as f64 / self.table.len() as f64
+    }
+
+    /// Returns an estimate of the occupancy of the table in permille, based on its first 1000 entries (in the manner of the UCI
+    /// `hashfull` value)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use game_player::transposition_table::TranspositionTable;
+    /// let mut table = TranspositionTable::new(100000, 10);
+    /// for fingerprint in 0..50000 {
+    ///     table.update(fingerprint, 1.0, 5);
+    /// }
+    /// assert_eq!(table.hashfull(), 1000);
+    /// ```
+    pub fn hashfull(&self) -> u32 {
+        let sample = self.table.len().min(1000);
+        (self.used(sample) * 1000 / sample) as u32
+    }
+
+    /// Returns the statistics accumulated since the table was created or the statistics were last reset
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use game_player::transposition_table::TranspositionTable;
+    /// let mut table = TranspositionTable::new(100, 10);
+    /// table.update(1, 1.0, 5);
+    /// table.update(1, 2.0, 3); // Rejected
+    /// table.check(1, -1);
+    /// table.check(2, -1);
+    ///
+    /// let statistics = table.statistics();
+    /// assert_eq!((statistics.probes, statistics.hits, statistics.misses), (2, 1, 1));
+    /// assert_eq!(statistics.rejected, 1);
+    /// ```
+    #[cfg(feature = "transposition_table_statistics")]
+    pub fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    /// Resets the statistics
+    #[cfg(feature = "transposition_table_statistics")]
+    pub fn reset_statistics(&mut self) {
+        self.statistics = Statistics::default();
+    }
+
+    /// Changes the number of entries in the table
+    ///
+    /// The entries that have not expired are moved to the resized table in order of quality (highest first, and then the most
+    /// recently referenced), so if the table is shrunk, an entry is dropped only when its bucket is already filled by
+    /// entries of equal or higher quality. An entry keeps its position in its bucket unless that position is already taken,
+    /// since a replacement policy may depend on it (see [`TwoTier`](replacement::TwoTier)).
+    ///
+    /// # Arguments
+    /// * `size` - Number of entries in the table. It is rounded up to a multiple of the number of ways.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use game_player::transposition_table::TranspositionTable;
+    /// let mut table = TranspositionTable::new(4, 10);
+    /// table.update(1, 1.0, 5);
+    /// table.update(2, 2.0, 3);
+    ///
+    /// // 1 and 2 map to the same entry of the smaller table, so the one of higher quality is kept
+    /// table.resize(1);
+    /// assert_eq!(table.check(1, -1), Some((1.0, 5)));
+    /// assert_eq!(table.check(2, -1), None);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0 or the table cannot be allocated.
+    pub fn resize(&mut self, size: usize) {
+        assert!(size > 0);
+        self.reallocate(size).expect("the table is too large to allocate");
+    }
+
+    /// Changes the number of entries in the table to fit a memory budget (see [`with_memory`](Self::with_memory) and
+    /// [`resize`](Self::resize))
+    ///
+    /// # Arguments
+    /// * `bytes` - The memory budget of the table's entries, in bytes
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is less than the size of one bucket.
+    pub fn resize_to_memory(&mut self, bytes: usize) {
+        self.resize(Self::size_for_memory(bytes, self.ways));
+    }
+
+    /// Changes the number of entries in the table, or returns an error instead of panicking (see [`resize`](Self::resize))
+    ///
+    /// # Errors
+    /// Returns [`TableError::ZeroSize`] if `size` is 0, or [`TableError::TooLarge`] if the table cannot be allocated. The
+    /// table is unchanged.
+    pub fn try_resize(&mut self, size: usize) -> Result<(), TableError> {
+        if size == 0 {
+            return Err(TableError::ZeroSize);
         }
+        self.reallocate(size)
+    }
+
+    /// Changes the number of entries in the table to fit a memory budget, or returns an error instead of panicking (see
+    /// [`resize_to_memory`](Self::resize_to_memory))
+    ///
+    /// # Errors
+    /// Returns [`TableError::InsufficientMemory`] if `bytes` is less than the size of one bucket, or [`TableError::TooLarge`]
+    /// if the table cannot be allocated. The table is unchanged.
+    pub fn try_resize_to_memory(&mut self, bytes: usize) -> Result<(), TableError> {
+        self.reallocate(Self::try_size_for_memory(bytes, self.ways)?)
     }
 
     /// Returns the value and quality of a state if they are stored in the table and its quality is above the specified minimum (if
@@ -116,9 +616,6 @@ impl TranspositionTable {
     /// # Returns
     /// optional result as (value, quality)
     ///
-    /// # Panics
-    /// Panics if `fingerprint` is `u64::MAX`.
-    ///
     /// # Side Effects
     /// * Resets the age of the entry to 0 if found.
     ///
@@ -142,24 +639,28 @@ impl TranspositionTable {
     /// // Check non-existent entry
     /// assert_eq!(table.check(99999, -1), None);
     /// ```
-    pub fn check(&mut self, fingerprint: u64, min_q: i16) -> Option<(f32, i16)> {
-        assert_ne!(fingerprint, Entry::UNUSED, "fingerprint != u64::MAX");
-
-        // Find the entry
-        let entry = self.find(fingerprint);
-        if entry.fingerprint != fingerprint {
-            return None; // Not found
-        }
-
-        // The entry was accessed so reset its age
-        entry.age = 0;
+    pub fn check(&mut self, fingerprint: u64, min_q: i16) -> Option<(T, i16)> {
+        self.check_with_key(fingerprint, 0, min_q)
+    }
 
-        // Check the quality if min_q >= 0
-        if min_q >= 0 && entry.q < min_q {
-            return None; // Insufficient quality
+    /// Like [`check`](Self::check), except that if the table has verification keys (see
+    /// [`with_verification`](Self::with_verification)), the entry is found only if its key matches too
+    ///
+    /// # Arguments
+    /// * `fingerprint` - Fingerprint of state to be checked for
+    /// * `key` - Verification key of the state. It is ignored if the table does not have verification keys.
+    /// * `min_q` - Minimum quality. If less than 0, it is not used.
+    pub fn check_with_key(&mut self, fingerprint: u64, key: u64, min_q: i16) -> Option<(T, i16)> {
+        let result = self.probe(fingerprint, key, min_q);
+        #[cfg(feature = "transposition_table_statistics")]
+        {
+            self.statistics.probes += 1;
+            match result {
+                Some(_) => self.statistics.hits += 1,
+                None => self.statistics.misses += 1,
+            }
         }
-
-        Some((entry.value, entry.q))
+        result
     }
 
     /// Updates (or adds) an entry in the table if its quality is greater than or equal to the existing entry's quality
@@ -170,7 +671,6 @@ impl TranspositionTable {
     /// * `quality` - Quality of the value
     ///
     /// # Panics
-    /// Panics if `fingerprint` is `u64::MAX`.
     /// Panics if `quality` is negative.
     ///
     /// # Examples
@@ -190,25 +690,60 @@ impl TranspositionTable {
     /// table.update(12345, 2.0, 7);
     /// assert_eq!(table.check(12345, -1), Some((2.0, 7))); // New value
     /// ```
-    pub fn update(&mut self, fingerprint: u64, value: f32, quality: i16) {
-        assert_ne!(fingerprint, Entry::UNUSED, "fingerprint != u64::MAX");
-        assert!(quality >= 0);
+    pub fn update(&mut self, fingerprint: u64, value: T, quality: i16) {
+        self.update_with_key(fingerprint, 0, value, quality);
+    }
 
-        // Find the entry for the fingerprint
-        let entry = self.find(fingerprint);
-        let is_unused = entry.fingerprint == Entry::UNUSED;
+    /// Updates (or adds) an entry in the table, or returns an error instead of panicking (see [`update`](Self::update))
+    ///
+    /// # Errors
+    /// Returns [`TableError::NegativeQuality`] if `quality` is negative. The table is unchanged.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use game_player::transposition_table::{TableError, TranspositionTable};
+    /// let mut table = TranspositionTable::new(100, 10);
+    /// assert_eq!(table.try_update(12345, 1.0, 5), Ok(()));
+    /// assert_eq!(table.try_update(12345, 1.0, -1), Err(TableError::NegativeQuality(-1)));
+    /// ```
+    pub fn try_update(&mut self, fingerprint: u64, value: T, quality: i16) -> Result<(), TableError> {
+        self.try_update_with_key(fingerprint, 0, value, quality)
+    }
 
-        // If the entry is unused or if the new quality >= the stored quality, then store the new value. Note: It is assumed to be
-        // better to replace values of equal quality in order to dispose of old entries that are less likely to be relevant.
+    /// Like [`update`](Self::update), except that if the table has verification keys (see
+    /// [`with_verification`](Self::with_verification)), the key is stored with the value
+    ///
+    /// # Arguments
+    /// * `fingerprint` - Fingerprint of state to be stored
+    /// * `key` - Verification key of the state. It is ignored if the table does not have verification keys.
+    /// * `value` - Value to be stored
+    /// * `quality` - Quality of the value
+    ///
+    /// # Panics
+    /// Panics if `quality` is negative.
+    pub fn update_with_key(&mut self, fingerprint: u64, key: u64, value: T, quality: i16) {
+        assert!(quality >= 0);
 
-        if is_unused || quality >= entry.q {
-            *entry = Entry {
-                fingerprint,
-                value,
-                q: quality,
-                age: 0,
-            };
+        // Find the entry for the fingerprint, or the entry that it replaces (as decided by the replacement policy). With the
+        // default policy, the value is stored if the entry is unused or if the new quality >= the stored quality. Note: It is
+        // assumed to be better to replace values of equal quality in order to dispose of old entries that are less likely to be
+        // relevant.
+        if let Some(i) = self.slot(fingerprint, key, quality, false) {
+            self.store(i, self.entry(fingerprint, value, quality), key);
+        }
+    }
+
+    /// Updates (or adds) an entry in the table with a verification key, or returns an error instead of panicking (see
+    /// [`update_with_key`](Self::update_with_key))
+    ///
+    /// # Errors
+    /// Returns [`TableError::NegativeQuality`] if `quality` is negative. The table is unchanged.
+    pub fn try_update_with_key(&mut self, fingerprint: u64, key: u64, value: T, quality: i16) -> Result<(), TableError> {
+        if quality < 0 {
+            return Err(TableError::NegativeQuality(quality));
         }
+        self.update_with_key(fingerprint, key, value, quality);
+        Ok(())
     }
 
     /// Sets an entry in the table.
@@ -221,7 +756,6 @@ impl TranspositionTable {
     /// * `quality` - Quality of the value
     ///
     /// # Panics
-    /// Panics if `fingerprint` is `u64::MAX`.
     /// Panics if `quality` is negative.
     ///
     /// # Examples
@@ -237,26 +771,60 @@ impl TranspositionTable {
     /// table.set(12345, 2.5, 3);
     /// assert_eq!(table.check(12345, -1), Some((2.5, 3)));
     /// ```
-    pub fn set(&mut self, fingerprint: u64, value: f32, quality: i16) {
-        assert_ne!(fingerprint, Entry::UNUSED, "fingerprint != u64::MAX");
+    pub fn set(&mut self, fingerprint: u64, value: T, quality: i16) {
+        self.set_with_key(fingerprint, 0, value, quality);
+    }
+
+    /// Sets an entry in the table, or returns an error instead of panicking (see [`set`](Self::set))
+    ///
+    /// # Errors
+    /// Returns [`TableError::NegativeQuality`] if `quality` is negative. The table is unchanged.
+    pub fn try_set(&mut self, fingerprint: u64, value: T, quality: i16) -> Result<(), TableError> {
+        self.try_set_with_key(fingerprint, 0, value, quality)
+    }
+
+    /// Like [`set`](Self::set), except that if the table has verification keys (see
+    /// [`with_verification`](Self::with_verification)), the key is stored with the value
+    ///
+    /// # Arguments
+    /// * `fingerprint` - Fingerprint of state to be stored
+    /// * `key` - Verification key of the state. It is ignored if the table does not have verification keys.
+    /// * `value` - Value to be stored
+    /// * `quality` - Quality of the value
+    ///
+    /// # Panics
+    /// Panics if `quality` is negative.
+    pub fn set_with_key(&mut self, fingerprint: u64, key: u64, value: T, quality: i16) {
         assert!(quality >= 0);
 
-        // Find the entry for the fingerprint
-        let entry = self.find(fingerprint);
+        // Find the entry for the fingerprint, or the entry that it replaces, and store the state, value and quality
+        if let Some(i) = self.slot(fingerprint, key, i16::MAX, true) {
+            self.store(i, self.entry(fingerprint, value, quality), key);
+        }
+    }
 
-        // Store the state, value and quality
-        *entry = Entry {
-            fingerprint,
-            value,
-            q: quality,
-            age: 0,
-        };
+    /// Sets an entry in the table with a verification key, or returns an error instead of panicking (see
+    /// [`set_with_key`](Self::set_with_key))
+    ///
+    /// # Errors
+    /// Returns [`TableError::NegativeQuality`] if `quality` is negative. The table is unchanged.
+    pub fn try_set_with_key(&mut self, fingerprint: u64, key: u64, value: T, quality: i16) -> Result<(), TableError> {
+        if quality < 0 {
+            return Err(TableError::NegativeQuality(quality));
+        }
+        self.set_with_key(fingerprint, key, value, quality);
+        Ok(())
     }
 
     /// The T-table is persistent. So in order to gradually dispose of entries that are no longer relevant, entries that have not
     /// been referenced for a while are removed.
     ///
-    /// This method increments the age of all entries and removes entries that exceed the maximum age.
+    /// This method increments the age of all entries. Entries that exceed the maximum age (or, in general, the entries that the
+    /// replacement policy considers expired) are no longer found and are replaced before any other entries.
+    ///
+    /// The ages are not stored in the entries. Instead, the table's generation is incremented and the age of an entry is the
+    /// number of generations since it was last referenced, so this method takes constant time, except that once every 32768
+    /// calls it removes the expired entries from the whole table so that the 16-bit generation stamps do not wrap around.
     ///
     /// # Examples
     ///
@@ -279,21 +847,388 @@ impl TranspositionTable {
     /// assert_eq!(table.check(12345, -1), None); // Entry aged out
     /// ```
     pub fn age(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+        if self.generation.is_multiple_of(SWEEP_INTERVAL) {
+            self.sweep();
+        }
+    }
+
+    // Removes the expired entries and any entries that are at least SWEEP_INTERVAL generations old
+    fn sweep(&mut self) {
+        let generation = self.generation;
         self.table
             .iter_mut()
-            .filter(|entry| entry.fingerprint != Entry::UNUSED)
+            .filter(|entry| !entry.is_unused())
             .for_each(|entry| {
-                entry.age += 1;
-                if entry.age > self.max_age {
+                if entry.age(generation) >= SWEEP_INTERVAL || self.policy.expired(&entry.slot(generation), self.max_age) {
                     entry.clear();
+                    #[cfg(feature = "transposition_table_statistics")]
+                    {
+                        self.statistics.evictions += 1;
+                    }
                 }
             });
     }
 
-    // Find the entry slot for a fingerprint using simple modulo hashing
-    fn find(&mut self, hash: u64) -> &mut Entry {
-        let i = (hash as usize) % self.table.len();
-        &mut self.table[i]
+    // Returns the value and quality of a state if they are stored in the table with sufficient quality (see `check`)
+    fn probe(&mut self, fingerprint: u64, key: u64, min_q: i16) -> Option<(T, i16)> {
+        // Find the entry
+        let generation = self.generation;
+        let i = self.find(fingerprint, key)?;
+        let entry = &mut self.table[i];
+
+        // The entry was accessed so reset its age
+        entry.generation = generation;
+
+        // Check the quality if min_q >= 0
+        if min_q >= 0 && entry.q < min_q {
+            return None; // Insufficient quality
+        }
+
+        Some((entry.value, entry.q))
+    }
+
+    // Returns true if an entry is in use and has not expired
+    fn is_live(&self, entry: &Entry<T>) -> bool {
+        !entry.is_unused() && !self.policy.expired(&entry.slot(self.generation), self.max_age)
+    }
+
+    // Returns true if the entry at an index is in use and holds the state with the fingerprint and verification key
+    fn matches(&self, i: usize, fingerprint: u64, key: u64) -> bool {
+        let entry = &self.table[i];
+        !entry.is_unused() && entry.fingerprint == fingerprint && (self.keys.is_empty() || self.keys[i] == key)
+    }
+
+    // Returns the verification key of the entry at an index, or 0 if the table does not have verification keys
+    fn key(&self, i: usize) -> u64 {
+        self.keys.get(i).copied().unwrap_or(0)
+    }
+
+    // Returns a new entry in the current generation
+    fn entry(&self, fingerprint: u64, value: T, quality: i16) -> Entry<T> {
+        Entry {
+            fingerprint,
+            value,
+            q: quality,
+            generation: self.generation,
+        }
+    }
+
+    // Stores an entry and its verification key at an index
+    fn store(&mut self, i: usize, entry: Entry<T>, key: u64) {
+        self.table[i] = entry;
+        if let Some(stored_key) = self.keys.get_mut(i) {
+            *stored_key = key;
+        }
+    }
+
+    // Returns the number of entries that are in use among the first `count` entries of the table
+    fn used(&self, count: usize) -> usize {
+        self.table[..count].iter().filter(|entry| self.is_live(entry)).count()
+    }
+
+    // Allocates an empty table of the given size, with or without verification keys, or returns an error if the memory cannot
+    // be allocated
+    fn allocate(size: usize, ways: usize, max_age: i16, verification: bool) -> Result<Self, TableError> {
+        let (table, keys) = Self::allocate_entries(size, verification)?;
+        Ok(Self {
+            table,
+            keys,
+            max_age,
+            ways,
+            policy: Box::new(DepthPreferred),
+            generation: 0,
+            #[cfg(feature = "transposition_table_statistics")]
+            statistics: Statistics::default(),
+        })
+    }
+
+    // Allocates the empty entries and keys of a table of the given size, or returns an error if the memory cannot be allocated
+    fn allocate_entries(size: usize, verification: bool) -> Result<(Vec<Entry<T>>, Vec<u64>), TableError> {
+        let mut table = Vec::new();
+        table.try_reserve_exact(size).map_err(|_| TableError::TooLarge(size))?;
+        table.resize(size, Entry::default());
+        let mut keys = Vec::new();
+        if verification {
+            keys.try_reserve_exact(size).map_err(|_| TableError::TooLarge(size))?;
+            keys.resize(size, 0);
+        }
+        Ok((table, keys))
+    }
+
+    // Returns the given number of entries rounded up to a multiple of the number of ways, saturating rather than overflowing
+    // so that the allocation of a huge table fails
+    fn round_size(size: usize, ways: usize) -> usize {
+        size.div_ceil(ways).saturating_mul(ways)
+    }
+
+    // Changes the number of entries in the table, keeping as many of the entries as fit. The table is unchanged if the new
+    // entries cannot be allocated.
+    fn reallocate(&mut self, size: usize) -> Result<(), TableError> {
+        let (table, keys) = Self::allocate_entries(Self::round_size(size, self.ways), !self.keys.is_empty())?;
+        let mut entries: Vec<(Entry<T>, u64, usize)> = (0..self.table.len())
+            .filter(|&i| self.is_live(&self.table[i]))
+            .map(|i| (self.table[i], self.key(i), i % self.ways))
+            .collect();
+        #[cfg(feature = "transposition_table_statistics")]
+        {
+            let used = self.table.iter().filter(|entry| !entry.is_unused()).count();
+            self.statistics.evictions += (used - entries.len()) as u64;
+        }
+        entries.sort_by_key(|(entry, _, _)| (Reverse(entry.q), entry.age(self.generation)));
+
+        self.table = table;
+        self.keys = keys;
+        for (entry, key, way) in entries {
+            // An entry keeps its position in its bucket if possible, since the replacement policy may depend on it
+            let mut bucket = self.bucket(entry.fingerprint);
+            let preferred = bucket.start + way;
+            let i = if self.table[preferred].is_unused() {
+                Some(preferred)
+            } else {
+                bucket.find(|&i| self.table[i].is_unused())
+            };
+            if let Some(i) = i {
+                self.store(i, entry, key);
+            }
+        }
+        Ok(())
+    }
+
+    // Returns the range of the bucket for a fingerprint using simple modulo hashing. If the number of buckets is a power of
+    // two, the modulo is a mask.
+    fn bucket(&self, hash: u64) -> Range<usize> {
+        let buckets = self.table.len() / self.ways;
+        let i = if buckets.is_power_of_two() {
+            (hash as usize) & (buckets - 1)
+        } else {
+            (hash as usize) % buckets
+        } * self.ways;
+        i..i + self.ways
+    }
+
+    // Returns the number of entries in the largest power-of-two number of buckets that fit in the given number of bytes
+    fn try_size_for_memory(bytes: usize, ways: usize) -> Result<usize, TableError> {
+        let bucket = ways * size_of::<Entry<T>>();
+        if bytes < bucket {
+            return Err(TableError::InsufficientMemory { bytes, bucket });
+        }
+        Ok(Self::size_for_memory(bytes, ways))
+    }
+
+    fn validate_ways(ways: usize) -> Result<(), TableError> {
+        if ways == 0 || ways > MAX_WAYS {
+            return Err(TableError::InvalidWays(ways));
+        }
+        Ok(())
+    }
+
+    fn size_for_memory(bytes: usize, ways: usize) -> usize {
+        assert!(ways > 0);
+        let buckets = bytes / (ways * size_of::<Entry<T>>());
+        assert!(buckets > 0, "the memory budget is less than one bucket");
+        (1 << buckets.ilog2()) * ways
+    }
+
+    // Returns the index of the entry for a fingerprint and verification key, if it is in the table and has not expired. An
+    // expired entry is removed.
+    fn find(&mut self, fingerprint: u64, key: u64) -> Option<usize> {
+        let i = self.bucket(fingerprint).find(|&i| self.matches(i, fingerprint, key))?;
+        let entry = &mut self.table[i];
+        if self.policy.expired(&entry.slot(self.generation), self.max_age) {
+            entry.clear();
+            #[cfg(feature = "transposition_table_statistics")]
+            {
+                self.statistics.evictions += 1;
+            }
+            return None;
+        }
+        Some(i)
+    }
+
+    // Returns the index of the entry in which a value of the state with the given quality is to be stored, or None if it is
+    // not to be stored. If the state is in the table, its entry is returned if the replacement policy allows the value to be
+    // replaced (or always, if `replace` is true). Otherwise, an unused (or expired) entry in its bucket is returned or, if
+    // there is none, the entry chosen by the replacement policy.
+    fn slot(&mut self, fingerprint: u64, key: u64, quality: i16, replace: bool) -> Option<usize> {
+        let range = self.bucket(fingerprint);
+        let bucket = &self.table[range.clone()];
+        let mut slots = [Slot { quality: 0, age: 0 }; MAX_WAYS];
+        let mut live = [false; MAX_WAYS];
+        for (i, entry) in bucket.iter().enumerate() {
+            slots[i] = entry.slot(self.generation);
+            live[i] = !entry.is_unused() && !self.policy.expired(&slots[i], self.max_age);
+        }
+        let slots = &slots[..bucket.len()];
+
+        let i = if let Some(i) = (0..bucket.len()).find(|&i| live[i] && self.matches(range.start + i, fingerprint, key)) {
+            if !replace && !self.policy.replaces(slots, i, quality) {
+                #[cfg(feature = "transposition_table_statistics")]
+                {
+                    self.statistics.rejected += 1;
+                }
+                return None;
+            }
+            i
+        } else if let Some(i) = (0..bucket.len()).find(|&i| !live[i]) {
+            #[cfg(feature = "transposition_table_statistics")]
+            if !bucket[i].is_unused() {
+                self.statistics.evictions += 1;
+            }
+            i
+        } else {
+            // A value that is set replaces the first entry if the policy does not choose one
+            let victim = self.policy.victim(slots, quality).or(replace.then_some(0));
+            #[cfg(feature = "transposition_table_statistics")]
+            match victim {
+                Some(_) => self.statistics.collisions += 1,
+                None => self.statistics.rejected += 1,
+            }
+            let victim = victim?;
+
+            // The policy may keep the replaced entry by moving it to another entry of the bucket
+            if let Some(j) = self.policy.demote(slots, victim).filter(|&j| j != victim) {
+                self.store(range.start + j, self.table[range.start + victim], self.key(range.start + victim));
+            }
+            victim
+        };
+        Some(range.start + i)
+    }
+}
+
+// Persistence requires values that can be converted to and from bits (see `Score::to_bits`)
+impl<T: Score> TranspositionTable<T> {
+    /// Saves the table to a file (see [Table Format](self#table-format)).
+    ///
+    /// Only the entries that are in use are saved. The replacement policy and the statistics are not saved.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be created or written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Loads a table from a file. The table must have been saved with the same value type.
+    ///
+    /// The loaded table uses the default replacement policy.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not a table of a supported version, or if the checksum does not match.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Writes the table in the table format.
+    ///
+    /// # Errors
+    /// Returns an error if the writer fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use game_player::transposition_table::TranspositionTable;
+    /// let mut table = TranspositionTable::new(100, 10);
+    /// table.update(12345, 1.5, 5);
+    ///
+    /// let mut bytes = Vec::new();
+    /// table.write_to(&mut bytes).unwrap();
+    /// let mut loaded: TranspositionTable = TranspositionTable::read_from(&mut bytes.as_slice()).unwrap();
+    /// assert_eq!(loaded.check(12345, -1), Some((1.5, 5)));
+    /// ```
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let live = || (0..self.table.len()).filter(|&i| self.is_live(&self.table[i]));
+        let verification = !self.keys.is_empty();
+
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&(self.table.len() as u64).to_le_bytes());
+        header.extend_from_slice(&(self.ways as u32).to_le_bytes());
+        header.extend_from_slice(&self.max_age.to_le_bytes());
+        header.extend_from_slice(&(if verification { FLAG_VERIFICATION } else { 0 }).to_le_bytes());
+        header.extend_from_slice(&(live().count() as u64).to_le_bytes());
+
+        // The checksum is computed before anything is written so that it can be in the header
+        let record = |i: usize| {
+            let key = if verification { &self.keys[i].to_le_bytes()[..] } else { &[] };
+            [&self.table[i].record(self.generation, i % self.ways)[..], key].concat()
+        };
+        let checksum = live().fold(fnv1a(FNV_OFFSET_BASIS, &header), |checksum, i| fnv1a(checksum, &record(i)));
+
+        writer.write_all(&header)?;
+        writer.write_all(&checksum.to_le_bytes())?;
+        for i in live() {
+            writer.write_all(&record(i))?;
+        }
+        Ok(())
+    }
+
+    /// Reads a table in the table format.
+    ///
+    /// # Errors
+    /// Returns an error if the reader fails, the data is not a table of a supported version, the checksum does not match, or
+    /// the table cannot be allocated.
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let mut header = [0; HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+        if &header[0..4] != MAGIC {
+            return Err(invalid("not a transposition table"));
+        }
+        if u32::from_le_bytes(header[4..8].try_into().unwrap()) != VERSION {
+            return Err(invalid("unsupported transposition table version"));
+        }
+        let size = usize::try_from(u64::from_le_bytes(header[8..16].try_into().unwrap()))
+            .map_err(|_| invalid("transposition table is too large"))?;
+        let ways = u32::from_le_bytes(header[16..20].try_into().unwrap()) as usize;
+        let max_age = i16::from_le_bytes(header[20..22].try_into().unwrap());
+        let flags = u16::from_le_bytes(header[22..24].try_into().unwrap());
+        let count = u64::from_le_bytes(header[24..32].try_into().unwrap());
+        if size == 0
+            || ways == 0
+            || ways > MAX_WAYS
+            || size % ways != 0
+            || max_age <= 0
+            || flags & !FLAG_VERIFICATION != 0
+            || count > size as u64
+        {
+            return Err(invalid("invalid transposition table header"));
+        }
+        let mut checksum = [0; 8];
+        reader.read_exact(&mut checksum)?;
+
+        // The records are read and checked before the table is allocated, so that a corrupt header cannot cause a large
+        // allocation. The size of the buffer is limited by the amount of data actually read.
+        let verification = flags & FLAG_VERIFICATION != 0;
+        let record_size = RECORD_SIZE + if verification { size_of::<u64>() } else { 0 };
+        let length = count.checked_mul(record_size as u64).ok_or_else(|| invalid("transposition table is too large"))?;
+        let mut records = Vec::new();
+        reader.take(length).read_to_end(&mut records)?;
+        if records.len() as u64 != length {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "transposition table is truncated"));
+        }
+        if fnv1a(fnv1a(FNV_OFFSET_BASIS, &header), &records) != u64::from_le_bytes(checksum) {
+            return Err(invalid("transposition table checksum mismatch"));
+        }
+
+        let mut table = Self::allocate(size, ways, max_age, verification)
+            .map_err(|_| io::Error::new(io::ErrorKind::OutOfMemory, "transposition table is too large"))?;
+        for record in records.chunks_exact(record_size) {
+            let (entry, way) = Entry::from_record(record[..RECORD_SIZE].try_into().unwrap(), table.generation)
+                .ok_or_else(|| invalid("invalid transposition table entry"))?;
+            let key = if verification { u64::from_le_bytes(record[RECORD_SIZE..].try_into().unwrap()) } else { 0 };
+
+            // The entry is restored to the same position in its bucket, since the replacement policy may depend on it
+            let i = table.bucket(entry.fingerprint).start + way;
+            if way >= ways || !table.table[i].is_unused() {
+                return Err(invalid("invalid transposition table entry"));
+            }
+            table.store(i, entry, key);
+        }
+        Ok(table)
     }
 }
 
@@ -303,32 +1238,151 @@ mod tests {
 
     #[test]
     fn test_new_valid_parameters() {
-        let table = TranspositionTable::new(100, 10);
+        let table = TranspositionTable::<f32>::new(100, 10);
         assert_eq!(table.table.len(), 100);
         assert_eq!(table.max_age, 10);
+        assert_eq!(table.ways, 1);
+    }
+
+    #[test]
+    fn test_with_ways_rounds_up_size() {
+        let table = TranspositionTable::<f32>::with_ways(10, 4, 10);
+        assert_eq!(table.table.len(), 12);
+        assert_eq!(table.ways, 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: ways > 0")]
+    fn test_with_ways_zero_ways_panics() {
+        TranspositionTable::<f32>::with_ways(100, 0, 10);
+    }
+
+    #[test]
+    fn test_bucket_keeps_colliding_entries() {
+        // 2 buckets of 4 entries. Even fingerprints map to bucket 0.
+        let mut table = TranspositionTable::with_ways(8, 4, 10);
+        for fingerprint in [2, 4, 6, 8] {
+            table.update(fingerprint, fingerprint as f32, 5);
+        }
+        for fingerprint in [2, 4, 6, 8] {
+            assert_eq!(table.check(fingerprint, -1), Some((fingerprint as f32, 5)));
+        }
+
+        // Odd fingerprints map to bucket 1, which is empty
+        table.update(1, 1.0, 0);
+        assert_eq!(table.check(1, -1), Some((1.0, 0)));
+        assert_eq!(table.check(2, -1), Some((2.0, 5)));
+    }
+
+    #[test]
+    fn test_bucket_replaces_lowest_quality() {
+        let mut table = TranspositionTable::with_ways(4, 4, 10);
+        table.update(1, 1.0, 3);
+        table.update(2, 2.0, 1);
+        table.update(3, 3.0, 4);
+        table.update(4, 4.0, 2);
+
+        // A new entry replaces the entry with the lowest quality if its quality is at least as high
+        table.update(5, 5.0, 0);
+        assert_eq!(table.check(5, -1), None);
+        table.update(5, 5.0, 1);
+        assert_eq!(table.check(5, -1), Some((5.0, 1)));
+        assert_eq!(table.check(2, -1), None);
+
+        // Updating an entry in the bucket does not replace another entry
+        table.update(1, 1.5, 3);
+        assert_eq!(table.check(1, -1), Some((1.5, 3)));
+        assert_eq!(table.check(4, -1), Some((4.0, 2)));
+        assert_eq!(table.check(5, -1), Some((5.0, 1)));
+    }
+
+    #[test]
+    fn test_bucket_replaces_oldest_of_equal_quality() {
+        let mut table = TranspositionTable::with_ways(2, 2, 10);
+        table.set(1, 1.0, 2);
+        table.set(2, 2.0, 2);
+
+        // 1 is referenced after aging, so 2 is older
+        table.age();
+        assert!(table.check(1, -1).is_some());
+
+        table.set(3, 3.0, 0);
+        assert_eq!(table.check(1, -1), Some((1.0, 2)));
+        assert_eq!(table.check(2, -1), None);
+        assert_eq!(table.check(3, -1), Some((3.0, 0)));
+    }
+
+    #[test]
+    fn test_fallible_api() {
+        assert_eq!(TranspositionTable::<f32>::try_new(0, 10).err(), Some(TableError::ZeroSize));
+        assert_eq!(TranspositionTable::<f32>::try_new(10, 0).err(), Some(TableError::InvalidMaxAge(0)));
+        assert_eq!(TranspositionTable::<f32>::try_with_ways(10, 0, 10).err(), Some(TableError::InvalidWays(0)));
+        assert_eq!(
+            TranspositionTable::<f32>::try_with_ways(10, MAX_WAYS + 1, 10).err(),
+            Some(TableError::InvalidWays(MAX_WAYS + 1))
+        );
+        assert_eq!(TranspositionTable::<f32>::try_with_memory(1 << 20, 0, 10).err(), Some(TableError::InvalidWays(0)));
+        assert_eq!(TranspositionTable::<f32>::try_new(usize::MAX / 2, 10).err(), Some(TableError::TooLarge(usize::MAX / 2)));
+        assert_eq!(TranspositionTable::<f32>::try_with_ways(usize::MAX, 3, 10).err(), Some(TableError::TooLarge(usize::MAX)));
+        assert_eq!(
+            TranspositionTable::<i16>::try_with_memory(27, 2, 10).err(),
+            Some(TableError::InsufficientMemory { bytes: 27, bucket: 28 })
+        );
+
+        let mut table = TranspositionTable::<f32>::try_with_memory(1 << 10, 2, 10).unwrap();
+        assert_eq!(table.len(), 64);
+        assert_eq!(table.try_update(1, 1.0, -1), Err(TableError::NegativeQuality(-1)));
+        assert_eq!(table.try_set(1, 1.0, -2), Err(TableError::NegativeQuality(-2)));
+        assert_eq!(table.check(1, -1), None);
+        assert_eq!(table.try_set(1, 1.0, 0), Ok(()));
+        assert_eq!(table.try_update(1, 2.0, 1), Ok(()));
+        assert_eq!(table.check(1, -1), Some((2.0, 1)));
+
+        assert_eq!(table.try_resize(0), Err(TableError::ZeroSize));
+        assert_eq!(table.try_resize(usize::MAX), Err(TableError::TooLarge(usize::MAX)));
+        assert_eq!(table.try_resize_to_memory(31), Err(TableError::InsufficientMemory { bytes: 31, bucket: 32 }));
+        assert_eq!(table.len(), 64);
+        assert_eq!(table.try_resize_to_memory(100), Ok(()));
+        assert_eq!(table.len(), 4);
+        assert_eq!(table.check(1, -1), Some((2.0, 1)));
+
+        assert_eq!(TableError::InvalidWays(17).to_string(), "the number of ways must be from 1 to 16, not 17");
+    }
+
+    #[test]
+    fn test_fallible_keyed_api() {
+        let mut table = TranspositionTable::<f32>::try_new(100, 10).unwrap().with_verification();
+        assert_eq!(table.try_update_with_key(1, 7, 1.0, -1), Err(TableError::NegativeQuality(-1)));
+        assert_eq!(table.try_set_with_key(1, 7, 1.0, -2), Err(TableError::NegativeQuality(-2)));
+        assert_eq!(table.check_with_key(1, 7, -1), None);
+
+        assert_eq!(table.try_set_with_key(1, 7, 1.0, 0), Ok(()));
+        assert_eq!(table.try_update_with_key(1, 7, 2.0, 1), Ok(()));
+        assert_eq!(table.check_with_key(1, 7, -1), Some((2.0, 1)));
+        assert_eq!(table.check_with_key(1, 8, -1), None);
     }
 
     #[test]
     #[should_panic(expected = "assertion failed: size > 0")]
     fn test_new_zero_size_panics() {
-        TranspositionTable::new(0, 10);
+        TranspositionTable::<f32>::new(0, 10);
     }
 
     #[test]
     #[should_panic(expected = "assertion failed: max_age > 0")]
     fn test_new_zero_max_age_panics() {
-        TranspositionTable::new(100, 0);
+        TranspositionTable::<f32>::new(100, 0);
     }
 
     #[test]
     #[should_panic(expected = "assertion failed: max_age > 0")]
     fn test_new_negative_max_age_panics() {
-        TranspositionTable::new(100, -1);
+        TranspositionTable::<f32>::new(100, -1);
     }
 
     #[test]
     fn test_check_nonexistent_entry() {
-        let mut table = TranspositionTable::new(100, 10);
+        let mut table = TranspositionTable::<f32>::new(100, 10);
         assert_eq!(table.check(12345, -1), None);
         assert_eq!(table.check(12345, 0), None);
         assert_eq!(table.check(12345, 5), None);
@@ -400,6 +1454,71 @@ mod tests {
         assert_eq!(table.check(12345, -1), Some((3.0, 7)));
     }
 
+    #[test]
+    fn test_set_replaces_first_entry_if_policy_rejects() {
+        struct NeverReplace;
+
+        impl ReplacementPolicy for NeverReplace {
+            fn victim(&self, _bucket: &[Slot], _quality: i16) -> Option<usize> {
+                None
+            }
+
+            fn replaces(&self, _bucket: &[Slot], _index: usize, _quality: i16) -> bool {
+                false
+            }
+        }
+
+        let mut table = TranspositionTable::with_ways(2, 2, 10).with_policy(NeverReplace);
+        table.update(1, 1.0, 0);
+        table.update(2, 2.0, 0);
+        table.update(3, 3.0, 9);
+        assert_eq!(table.check(3, -1), None);
+
+        table.set(3, 3.0, 9);
+        assert_eq!(table.check(3, -1), Some((3.0, 9)));
+        assert_eq!(table.check(1, -1), None);
+        assert_eq!(table.check(2, -1), Some((2.0, 0)));
+    }
+
+    #[test]
+    fn test_two_tier_demotes_replaced_entry() {
+        let mut table = TranspositionTable::with_ways(2, 2, 10).with_policy(replacement::TwoTier);
+        table.update(1, 1.0, 5);
+        table.update(2, 2.0, 3);
+
+        // 3 replaces 1 in the depth-preferred entry, and 1 replaces 2 in the always-replace entry
+        table.update(3, 3.0, 7);
+        assert_eq!({ table.table[0].fingerprint }, 3);
+        assert_eq!({ table.table[1].fingerprint }, 1);
+        assert_eq!(table.check(1, -1), Some((1.0, 5)));
+        assert_eq!(table.check(2, -1), None);
+
+        // 4 has a lower quality, so it replaces 1 in the always-replace entry
+        table.update(4, 4.0, 0);
+        assert_eq!(table.check(3, -1), Some((3.0, 7)));
+        assert_eq!(table.check(4, -1), Some((4.0, 0)));
+        assert_eq!(table.check(1, -1), None);
+    }
+
+    #[test]
+    fn test_resize_and_read_keep_positions() {
+        // 4 is in the second entry of the bucket even though its quality is higher than the quality of 2
+        let mut table = TranspositionTable::with_ways(2, 2, 10).with_policy(replacement::TwoTier);
+        table.update(2, 2.0, 1);
+        table.update(4, 4.0, 9);
+        assert_eq!((table.table[0].fingerprint, table.table[1].fingerprint), (2, 4));
+
+        table.resize(4);
+        assert_eq!((table.table[0].fingerprint, table.table[1].fingerprint), (2, 4));
+        table.resize(2);
+        assert_eq!((table.table[0].fingerprint, table.table[1].fingerprint), (2, 4));
+
+        let mut bytes = Vec::new();
+        table.write_to(&mut bytes).unwrap();
+        let loaded = TranspositionTable::<f32>::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!((loaded.table[0].fingerprint, loaded.table[1].fingerprint), (2, 4));
+    }
+
     #[test]
     fn test_age_resets_on_check() {
         let mut table = TranspositionTable::new(100, 2);
@@ -431,6 +1550,236 @@ mod tests {
         assert_eq!(table.check(12345, -1), None);
     }
 
+    #[test]
+    fn test_with_memory_rounds_down_to_power_of_two() {
+        // 1000 bytes fit 62 entries of 16 bytes, or 15 buckets of 4 entries
+        let table = TranspositionTable::<f32>::with_memory(1000, 4, 10);
+        assert_eq!(table.len(), 32);
+        assert_eq!(table.memory(), 512);
+
+        let table = TranspositionTable::<i16>::with_memory(1000, 1, 10);
+        assert_eq!(table.len(), 64);
+        assert_eq!(table.memory(), 64 * 14);
+    }
+
+    #[test]
+    #[should_panic(expected = "the memory budget is less than one bucket")]
+    fn test_with_memory_too_small_panics() {
+        TranspositionTable::<f32>::with_memory(63, 4, 10);
+    }
+
+    #[test]
+    fn test_resize_keeps_highest_quality() {
+        let mut table = TranspositionTable::with_ways(16, 2, 10);
+        for fingerprint in 1..=8 {
+            table.update(fingerprint, fingerprint as f32, fingerprint as i16);
+        }
+
+        // A single bucket of 2 entries keeps the 2 entries of highest quality
+        table.resize(2);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.check(8, -1), Some((8.0, 8)));
+        assert_eq!(table.check(7, -1), Some((7.0, 7)));
+        assert!((1..=6).all(|fingerprint| table.check(fingerprint, -1).is_none()));
+
+        // Growing keeps all of the entries
+        table.resize(64);
+        assert_eq!(table.len(), 64);
+        assert_eq!(table.check(8, -1), Some((8.0, 8)));
+        assert_eq!(table.check(7, -1), Some((7.0, 7)));
+    }
+
+    #[test]
+    fn test_resize_drops_expired_entries() {
+        let mut table = TranspositionTable::new(8, 1);
+        table.set(1, 1.0, 9);
+        table.age();
+        table.age();
+        table.set(2, 2.0, 0);
+
+        // 1 has expired, so 2 is kept even though they map to the same entry
+        table.resize(1);
+        assert_eq!(table.check(1, -1), None);
+        assert_eq!(table.check(2, -1), Some((2.0, 0)));
+    }
+
+    #[test]
+    fn test_occupancy_and_hashfull() {
+        let mut table = TranspositionTable::new(2000, 1);
+        assert_eq!(table.occupancy(), 0.0);
+        assert_eq!(table.hashfull(), 0);
+
+        // Fingerprints below 1000 are in the sample
+        for fingerprint in 0..500 {
+            table.update(fingerprint, 1.0, 1);
+        }
+        table.update(1500, 1.0, 1);
+        assert_eq!(table.occupancy(), 501.0 / 2000.0);
+        assert_eq!(table.hashfull(), 500);
+
+        // Expired entries are not counted
+        table.age();
+        table.age();
+        assert_eq!(table.occupancy(), 0.0);
+        assert_eq!(table.hashfull(), 0);
+    }
+
+    #[cfg(feature = "transposition_table_statistics")]
+    #[test]
+    fn test_statistics() {
+        let mut table = TranspositionTable::new(2, 1);
+        table.update(1, 1.0, 5);
+        table.update(1, 1.0, 4); // Rejected
+        table.update(3, 3.0, 4); // Rejected
+        table.update(3, 3.0, 5); // Replaces 1
+        table.update(2, 2.0, 5);
+        table.age();
+        table.age();
+        table.update(4, 4.0, 0); // Evicts 2
+        assert_eq!(table.check(4, 1), None);
+        assert_eq!(table.check(4, -1), Some((4.0, 0)));
+        assert_eq!(table.check(3, -1), None); // Evicts 3
+        assert_eq!(
+            *table.statistics(),
+            Statistics {
+                probes: 3,
+                hits: 1,
+                misses: 2,
+                rejected: 2,
+                collisions: 1,
+                evictions: 2,
+            }
+        );
+
+        table.reset_statistics();
+        assert_eq!(*table.statistics(), Statistics::default());
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let mut table = TranspositionTable::<i32>::with_ways(8, 2, 3);
+        table.update(1, -123_456_789, 5);
+        table.update(2, 7, 0);
+        table.age();
+        table.update(3, 8, 1);
+
+        let mut bytes = Vec::new();
+        table.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), HEADER_SIZE + 8 + 3 * RECORD_SIZE);
+
+        let mut loaded = TranspositionTable::<i32>::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!((loaded.len(), loaded.ways, loaded.max_age), (8, 2, 3));
+
+        // The ages are preserved, so 1 and 2 expire before 3
+        loaded.age();
+        loaded.age();
+        loaded.age();
+        assert_eq!(loaded.check(1, -1), None);
+        assert_eq!(loaded.check(2, -1), None);
+        assert_eq!(loaded.check(3, -1), Some((8, 1)));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut table = TranspositionTable::new(100, 10);
+        table.update(12345, 0.75, 5);
+
+        let path = std::env::temp_dir().join(format!("game_player_tt_{}.tt", std::process::id()));
+        table.save(&path).unwrap();
+        let loaded = TranspositionTable::<f32>::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap().check(12345, -1), Some((0.75, 5)));
+    }
+
+    #[test]
+    fn test_read_rejects_invalid_data() {
+        let mut table = TranspositionTable::new(100, 10);
+        table.update(12345, 0.75, 5);
+        let mut bytes = Vec::new();
+        table.write_to(&mut bytes).unwrap();
+        let read = |bytes: &[u8]| TranspositionTable::<f32>::read_from(&mut &bytes[..]).map(|_| ());
+
+        assert!(read(&bytes).is_ok());
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(read(&bad_magic).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 9;
+        assert_eq!(read(&bad_version).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let mut bad_ways = bytes.clone();
+        bad_ways[16] = 3;
+        assert_eq!(read(&bad_ways).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let mut bad_flags = bytes.clone();
+        bad_flags[22] = 2;
+        assert_eq!(read(&bad_flags).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let mut bad_way = bytes.clone();
+        bad_way[HEADER_SIZE + 8 + 16] = 1;
+        let checksum = fnv1a(FNV_OFFSET_BASIS, &[&bad_way[..HEADER_SIZE], &bad_way[HEADER_SIZE + 8..]].concat());
+        bad_way[HEADER_SIZE..HEADER_SIZE + 8].copy_from_slice(&checksum.to_le_bytes());
+        assert_eq!(read(&bad_way).unwrap_err().to_string(), "invalid transposition table entry");
+
+        let mut bad_value = bytes.clone();
+        bad_value[HEADER_SIZE + 8 + 8] ^= 1;
+        assert_eq!(read(&bad_value).unwrap_err().to_string(), "transposition table checksum mismatch");
+
+        let truncated = &bytes[..bytes.len() - 1];
+        assert_eq!(read(truncated).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_read_rejects_huge_size_without_allocating() {
+        // A header with a valid checksum and a huge size is rejected when the table is allocated
+        let mut bytes = Vec::new();
+        TranspositionTable::<f32>::new(100, 10).write_to(&mut bytes).unwrap();
+        bytes[8..16].copy_from_slice(&(1u64 << 60).to_le_bytes());
+        let checksum = fnv1a(FNV_OFFSET_BASIS, &bytes[..HEADER_SIZE]);
+        bytes[HEADER_SIZE..HEADER_SIZE + 8].copy_from_slice(&checksum.to_le_bytes());
+        let read = |bytes: &[u8]| TranspositionTable::<f32>::read_from(&mut &bytes[..]).map(|_| ());
+        assert_eq!(read(&bytes).unwrap_err().kind(), io::ErrorKind::OutOfMemory);
+
+        // A huge count is rejected when the records run out, before anything is allocated
+        bytes[24..32].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        bytes.extend_from_slice(&[0; RECORD_SIZE]);
+        assert_eq!(read(&bytes).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_expired_entry_is_replaced_first() {
+        let mut table = TranspositionTable::with_ways(2, 2, 1);
+        table.set(1, 1.0, 9);
+        table.age();
+        table.set(2, 2.0, 9);
+        table.age();
+
+        // 1 has expired, so it is replaced even though its quality is higher
+        table.update(3, 3.0, 0);
+        assert_eq!(table.check(1, -1), None);
+        assert_eq!(table.check(2, -1), Some((2.0, 9)));
+        assert_eq!(table.check(3, -1), Some((3.0, 0)));
+    }
+
+    #[test]
+    fn test_age_generation_wraps_around() {
+        let mut table = TranspositionTable::new(100, 2);
+        table.set(1, 1.0, 5);
+        table.set(2, 2.0, 5);
+
+        // After 65536 generations, the stamp of 1 would appear to be current if it had not been removed by a sweep
+        for _ in 0..=u16::MAX {
+            table.age();
+            if table.generation == 1 {
+                assert_eq!(table.check(2, -1), Some((2.0, 5)));
+            }
+        }
+        assert_eq!(table.check(1, -1), None);
+        assert_eq!(table.check(2, -1), None);
+    }
+
     #[test]
     fn test_multiple_entries() {
         let mut table = TranspositionTable::new(100, 10);
@@ -507,17 +1856,16 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "assertion `left != right` failed: fingerprint != u64::MAX")]
-    fn test_check_with_invalid_fingerprint() {
-        let mut table = TranspositionTable::new(100, 10);
-        table.check(u64::MAX, -1);
+    fn test_check_with_max_fingerprint() {
+        let mut table = TranspositionTable::<f32>::new(100, 10);
+        assert_eq!(table.check(u64::MAX, -1), None);
     }
 
     #[test]
-    #[should_panic(expected = "assertion `left != right` failed: fingerprint != u64::MAX")]
-    fn test_update_with_invalid_fingerprint() {
+    fn test_update_with_max_fingerprint() {
         let mut table = TranspositionTable::new(100, 10);
         table.update(u64::MAX, 1.0, 5);
+        assert_eq!(table.check(u64::MAX, -1), Some((1.0, 5)));
     }
 
     #[test]
@@ -528,10 +1876,47 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "assertion `left != right` failed: fingerprint != u64::MAX")]
-    fn test_set_with_invalid_fingerprint() {
+    fn test_set_with_max_fingerprint() {
         let mut table = TranspositionTable::new(100, 10);
         table.set(u64::MAX, 1.0, 5);
+        assert_eq!(table.check(u64::MAX, -1), Some((1.0, 5)));
+    }
+
+    #[test]
+    fn test_verification_keys() {
+        let mut table = TranspositionTable::with_ways(4, 2, 10).with_verification();
+        assert_eq!(table.memory(), 4 * 24);
+
+        // Two states with the same fingerprint and different keys have separate entries
+        table.update_with_key(1, 10, 1.0, 5);
+        table.update_with_key(1, 20, 2.0, 3);
+        assert_eq!(table.check_with_key(1, 10, -1), Some((1.0, 5)));
+        assert_eq!(table.check_with_key(1, 20, -1), Some((2.0, 3)));
+        assert_eq!(table.check_with_key(1, 30, -1), None);
+        assert_eq!(table.check(1, -1), None);
+
+        // A matching key is required to replace the value
+        table.set_with_key(1, 20, 2.5, 0);
+        assert_eq!(table.check_with_key(1, 20, -1), Some((2.5, 0)));
+        assert_eq!(table.check_with_key(1, 10, -1), Some((1.0, 5)));
+
+        // The keys are kept when the table is resized and when it is saved
+        table.resize(2);
+        let mut bytes = Vec::new();
+        table.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), HEADER_SIZE + 8 + 2 * (RECORD_SIZE + 8));
+        let mut loaded = TranspositionTable::<f32>::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(loaded.check_with_key(1, 10, -1), Some((1.0, 5)));
+        assert_eq!(loaded.check_with_key(1, 20, -1), Some((2.5, 0)));
+        assert_eq!(loaded.check_with_key(1, 0, -1), None);
+    }
+
+    #[test]
+    fn test_keys_ignored_without_verification() {
+        let mut table = TranspositionTable::new(4, 10);
+        table.update_with_key(1, 10, 1.0, 5);
+        assert_eq!(table.check_with_key(1, 20, -1), Some((1.0, 5)));
+        assert_eq!(table.check(1, -1), Some((1.0, 5)));
     }
 
     #[test]
@@ -556,6 +1941,43 @@ mod tests {
         assert_eq!(table.check(1000, -1), Some((100.0, 0)));
     }
 
+    #[test]
+    fn test_payload_values() {
+        #[derive(Clone, Copy, Default, Debug, PartialEq)]
+        struct Payload {
+            best_move: u16,
+            visits: u32,
+            value: f32,
+        }
+
+        let mut table = TranspositionTable::<Payload>::with_ways(8, 2, 2);
+        let payload = Payload {
+            best_move: 3,
+            visits: 100,
+            value: 0.5,
+        };
+        table.update(1, payload, 4);
+        table.update(1, Payload::default(), 3);
+        assert_eq!(table.check(1, -1), Some((payload, 4)));
+        assert_eq!(table.memory(), 8 * size_of::<Entry<Payload>>());
+
+        table.age();
+        table.age();
+        table.age();
+        assert_eq!(table.check(1, -1), None);
+    }
+
+    #[test]
+    fn test_integer_values() {
+        let mut table = TranspositionTable::<i16>::new(100, 10);
+
+        table.update(1, -300, 2);
+        table.update(2, i16::MAX, 4);
+        assert_eq!(table.check(1, -1), Some((-300, 2)));
+        assert_eq!(table.check(2, 3), Some((i16::MAX, 4)));
+        assert_eq!(table.check(2, 5), None);
+    }
+
     #[test]
     fn test_aging_multiple_entries() {
         let mut table = TranspositionTable::new(100, 3);
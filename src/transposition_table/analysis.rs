@@ -0,0 +1,195 @@
+//! Transposition Table Analysis
+//!
+//! This module exports the contents and statistics of a transposition table as JSON, for analysis by external tools. It is
+//! available only with the `analysis_transposition_table` feature.
+//!
+//! # JSON Format
+//! The exported object has these members:
+//! - `size`, `ways`, `max_age`, `generation`, `verification`: The configuration and current generation of the table, and
+//!   whether it has verification keys
+//! - `used`, `occupancy`: The number and fraction of entries that are in use (and have not expired)
+//! - `histograms`: Histograms of the entries that are in use
+//!   - `value`: `min` and `max` of the finite values, `counts` of the values in 16 equal-width bins from `min` to `max`, and
+//!     the number of `non_finite` values
+//!   - `quality`, `age`: Arrays of `[quality, count]` and `[age, count]` pairs, in ascending order
+//! - `buckets`: `counts[k]` is the number of buckets with `k` entries in use, and `full` is the number of full buckets
+//! - `statistics`: The table's statistics if the `transposition_table_statistics` feature is enabled (including the number of
+//!   `collisions`), otherwise `null`
+//! - `entries`: If requested, an array of the entries in use, each with its `fingerprint` (a 16-digit hexadecimal string),
+//!   `value` (in the table's value type), `quality` and `age`
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use serde_json::{Value, json};
+
+use super::TranspositionTable;
+use crate::score::Score;
+
+const VALUE_BINS: usize = 16;
+
+impl<T: Score> TranspositionTable<T> {
+    /// Returns the contents and statistics of the table as JSON (see [JSON Format](self#json-format)).
+    ///
+    /// # Arguments
+    /// * `include_entries` - If true, the entries in use are included. This can be very large.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use game_player::transposition_table::TranspositionTable;
+    /// let mut table = TranspositionTable::new(4, 10);
+    /// table.update(1, 1.5, 5);
+    ///
+    /// let json = table.to_json(true);
+    /// assert_eq!(json["used"], 1);
+    /// assert_eq!(json["entries"][0]["quality"], 5);
+    /// ```
+    pub fn to_json(&self, include_entries: bool) -> Value {
+        let live: Vec<(u64, T, i16, u16)> = self
+            .table
+            .iter()
+            .filter(|entry| self.is_live(entry))
+            .map(|entry| (entry.fingerprint, entry.value, entry.q, entry.age(self.generation)))
+            .collect();
+
+        let mut qualities = BTreeMap::new();
+        let mut ages = BTreeMap::new();
+        for &(_, _, quality, age) in &live {
+            *qualities.entry(quality).or_insert(0u64) += 1;
+            *ages.entry(age).or_insert(0u64) += 1;
+        }
+
+        let mut buckets = vec![0u64; self.ways + 1];
+        for bucket in self.table.chunks_exact(self.ways) {
+            buckets[bucket.iter().filter(|entry| self.is_live(entry)).count()] += 1;
+        }
+
+        #[cfg(feature = "transposition_table_statistics")]
+        let statistics = {
+            let statistics = &self.statistics;
+            json!({
+                "probes": statistics.probes,
+                "hits": statistics.hits,
+                "misses": statistics.misses,
+                "rejected": statistics.rejected,
+                "collisions": statistics.collisions,
+                "evictions": statistics.evictions,
+            })
+        };
+        #[cfg(not(feature = "transposition_table_statistics"))]
+        let statistics = Value::Null;
+
+        let mut json = json!({
+            "size": self.table.len(),
+            "ways": self.ways,
+            "max_age": self.max_age,
+            "generation": self.generation,
+            "verification": !self.keys.is_empty(),
+            "used": live.len(),
+            "occupancy": live.len() as f64 / self.table.len() as f64,
+            "histograms": {
+                "value": value_histogram(live.iter().map(|&(_, value, _, _)| value.to_f32())),
+                "quality": qualities.into_iter().collect::<Vec<_>>(),
+                "age": ages.into_iter().collect::<Vec<_>>(),
+            },
+            "buckets": {
+                "counts": buckets,
+                "full": buckets[self.ways],
+            },
+            "statistics": statistics,
+        });
+        if include_entries {
+            json["entries"] = live
+                .iter()
+                .map(|&(fingerprint, value, quality, age)| {
+                    json!({
+                        "fingerprint": format!("{fingerprint:016x}"),
+                        "value": value.to_json(),
+                        "quality": quality,
+                        "age": age,
+                    })
+                })
+                .collect();
+        }
+        json
+    }
+
+    /// Writes the contents and statistics of the table as JSON (see [`to_json`](Self::to_json)).
+    ///
+    /// # Errors
+    /// Returns an error if the writer fails.
+    pub fn write_json<W: Write>(&self, writer: &mut W, include_entries: bool) -> io::Result<()> {
+        serde_json::to_writer(&mut *writer, &self.to_json(include_entries))?;
+        writer.flush()
+    }
+}
+
+// Returns a histogram of the finite values in equal-width bins, and the number of values that are not finite
+fn value_histogram(values: impl Iterator<Item = f32>) -> Value {
+    let (finite, non_finite): (Vec<f32>, Vec<f32>) = values.partition(|value| value.is_finite());
+    let min = finite.iter().copied().min_by(f32::total_cmp);
+    let max = finite.iter().copied().max_by(f32::total_cmp);
+
+    let mut counts = [0u64; VALUE_BINS];
+    if let (Some(min), Some(max)) = (min, max) {
+        let width = (max - min) / VALUE_BINS as f32;
+        for value in &finite {
+            let bin = if width > 0.0 { ((value - min) / width) as usize } else { 0 };
+            counts[bin.min(VALUE_BINS - 1)] += 1;
+        }
+    }
+
+    json!({
+        "min": min,
+        "max": max,
+        "counts": counts,
+        "non_finite": non_finite.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let mut table = TranspositionTable::with_ways(8, 2, 2);
+        table.update(1, 0.0, 3);
+        table.update(5, 16.0, 3);
+        table.update(2, f32::INFINITY, 1);
+        table.age();
+        table.update(4, 4.0, 0);
+
+        let json = table.to_json(false);
+        assert_eq!(json["size"], 8);
+        assert_eq!(json["used"], 4);
+        assert_eq!(json["occupancy"], 0.5);
+        assert_eq!(json["histograms"]["value"]["min"], 0.0);
+        assert_eq!(json["histograms"]["value"]["max"], 16.0);
+        assert_eq!(json["histograms"]["value"]["counts"][0], 1);
+        assert_eq!(json["histograms"]["value"]["counts"][4], 1);
+        assert_eq!(json["histograms"]["value"]["counts"][15], 1);
+        assert_eq!(json["histograms"]["value"]["non_finite"], 1);
+        assert_eq!(json["histograms"]["quality"], json!([[0, 1], [1, 1], [3, 2]]));
+        assert_eq!(json["histograms"]["age"], json!([[0, 1], [1, 3]]));
+        assert_eq!(json["buckets"]["counts"], json!([1, 2, 1]));
+        assert_eq!(json["buckets"]["full"], 1);
+        assert!(json.get("entries").is_none());
+
+        let json = table.to_json(true);
+        assert_eq!(json["entries"].as_array().unwrap().len(), 4);
+        assert_eq!(json["entries"][0], json!({ "fingerprint": "0000000000000004", "value": 4.0, "quality": 0, "age": 0 }));
+    }
+
+    #[test]
+    fn test_write_json() {
+        let mut table = TranspositionTable::<i16>::new(4, 10);
+        table.update(1, -3, 5);
+
+        let mut bytes = Vec::new();
+        table.write_json(&mut bytes, true).unwrap();
+        let json: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json, table.to_json(true));
+        assert_eq!(json["entries"][0]["value"], -3);
+    }
+}
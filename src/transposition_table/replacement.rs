@@ -0,0 +1,261 @@
+//! Replacement Policies
+//!
+//! A replacement policy decides which entry of a full bucket is replaced by a new entry, whether a new value of a state
+//! replaces the value already stored for it, and which entries are removed when the table is aged.
+//!
+//! # Policies
+//! - [`DepthPreferred`]: An entry is replaced only by a value of equal or higher quality. This is the default.
+//! - [`AlwaysReplace`]: An entry is always replaced, and the oldest entry in a full bucket is the one replaced.
+//! - [`TwoTier`]: The first entry in each bucket is depth-preferred and the others are always replaced.
+//! - [`AgeWeighted`]: Like depth-preferred, except that the quality of an entry is reduced as it ages.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use game_player::transposition_table::TranspositionTable;
+//! use game_player::transposition_table::replacement::TwoTier;
+//!
+//! let mut table = TranspositionTable::with_ways(1024, 2, 10).with_policy(TwoTier);
+//! table.update(1, 1.0, 5);
+//! table.update(513, 2.0, 0); // Same bucket, lower quality: stored in the always-replace entry
+//! assert_eq!(table.check(1, -1), Some((1.0, 5)));
+//! assert_eq!(table.check(513, -1), Some((2.0, 0)));
+//! ```
+
+/// The quality and age of an entry in a bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slot {
+    /// The quality of the entry's value
+    pub quality: i16,
+    /// The number of times the table has been aged since the entry was referenced (at most `i16::MAX`)
+    pub age: i16,
+}
+
+/// An interface for transposition table replacement policies.
+///
+/// # Examples
+/// ```rust
+/// # use game_player::transposition_table::TranspositionTable;
+/// use game_player::transposition_table::replacement::{ReplacementPolicy, Slot};
+///
+/// // Keeps every entry until it ages out, and never replaces a value
+/// struct FirstComeFirstServed;
+///
+/// impl ReplacementPolicy for FirstComeFirstServed {
+///     fn victim(&self, _bucket: &[Slot], _quality: i16) -> Option<usize> {
+///         None
+///     }
+///
+///     fn replaces(&self, _bucket: &[Slot], _index: usize, _quality: i16) -> bool {
+///         false
+///     }
+/// }
+///
+/// let mut table = TranspositionTable::new(1, 10).with_policy(FirstComeFirstServed);
+/// table.update(1, 1.0, 0);
+/// table.update(2, 2.0, 9);
+/// assert_eq!(table.check(1, -1), Some((1.0, 0)));
+/// ```
+pub trait ReplacementPolicy {
+    /// Chooses the entry to be replaced by a new entry.
+    ///
+    /// This is called only if the state of the new entry is not in its bucket and the bucket has no unused entries.
+    /// [`TranspositionTable::set`](super::TranspositionTable::set) calls it with a quality of `i16::MAX`, and replaces the
+    /// first entry if it returns `None`.
+    ///
+    /// # Arguments
+    /// * `bucket` - The entries of the bucket
+    /// * `quality` - The quality of the new entry
+    ///
+    /// # Returns
+    /// The index in the bucket of the entry to be replaced, or `None` if the new entry is not stored
+    fn victim(&self, bucket: &[Slot], quality: i16) -> Option<usize>;
+
+    /// Returns true if a new value of a state replaces its stored value.
+    ///
+    /// This is called by [`TranspositionTable::update`](super::TranspositionTable::update) when the state is already in the
+    /// table.
+    ///
+    /// # Arguments
+    /// * `bucket` - The entries of the bucket
+    /// * `index` - The index in the bucket of the state's entry
+    /// * `quality` - The quality of the new value
+    fn replaces(&self, bucket: &[Slot], index: usize, quality: i16) -> bool;
+
+    /// Chooses the entry to which an entry chosen by [`victim`](Self::victim) is moved, instead of being discarded.
+    ///
+    /// # Arguments
+    /// * `bucket` - The entries of the bucket
+    /// * `index` - The index in the bucket of the entry being replaced
+    ///
+    /// # Returns
+    /// The index in the bucket of the entry that is replaced by the moved entry, or `None` if the entry is discarded. The
+    /// default is `None`.
+    fn demote(&self, _bucket: &[Slot], _index: usize) -> Option<usize> {
+        None
+    }
+
+    /// Returns true if an entry has expired. An expired entry is treated as unused: it is not found by
+    /// [`check`](super::TranspositionTable::check), and it is replaced before any entry that has not expired.
+    ///
+    /// # Arguments
+    /// * `slot` - The entry
+    /// * `max_age` - The maximum age of the table
+    ///
+    /// # Returns
+    /// True if the entry has expired. The default expires entries that are older than `max_age`.
+    fn expired(&self, slot: &Slot, max_age: i16) -> bool {
+        slot.age > max_age
+    }
+}
+
+/// Replaces an entry only with a value of equal or higher quality. In a full bucket, the entry with the lowest quality (and
+/// the oldest of those) is the one that may be replaced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DepthPreferred;
+
+impl ReplacementPolicy for DepthPreferred {
+    fn victim(&self, bucket: &[Slot], quality: i16) -> Option<usize> {
+        let i = lowest(bucket, |slot| slot.quality);
+        (quality >= bucket[i].quality).then_some(i)
+    }
+
+    fn replaces(&self, bucket: &[Slot], index: usize, quality: i16) -> bool {
+        quality >= bucket[index].quality
+    }
+}
+
+/// Always replaces an entry. In a full bucket, the oldest entry is replaced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AlwaysReplace;
+
+impl ReplacementPolicy for AlwaysReplace {
+    fn victim(&self, bucket: &[Slot], _quality: i16) -> Option<usize> {
+        Some(lowest(bucket, |_| 0))
+    }
+
+    fn replaces(&self, _bucket: &[Slot], _index: usize, _quality: i16) -> bool {
+        true
+    }
+}
+
+/// The first entry in each bucket is depth-preferred and the others are always replaced.
+///
+/// A new entry replaces the first entry if its quality is at least as high, and the replaced entry is moved to the oldest of
+/// the other entries. Otherwise, the new entry replaces the oldest of the other entries. This keeps the most valuable entry in
+/// each bucket while still storing recent entries. A table using this policy should have at least 2 ways.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TwoTier;
+
+impl ReplacementPolicy for TwoTier {
+    fn victim(&self, bucket: &[Slot], quality: i16) -> Option<usize> {
+        if quality >= bucket[0].quality {
+            Some(0)
+        } else if bucket.len() > 1 {
+            Some(1 + lowest(&bucket[1..], |_| 0))
+        } else {
+            None
+        }
+    }
+
+    fn replaces(&self, bucket: &[Slot], index: usize, quality: i16) -> bool {
+        index > 0 || quality >= bucket[0].quality
+    }
+
+    fn demote(&self, bucket: &[Slot], index: usize) -> Option<usize> {
+        (index == 0 && bucket.len() > 1).then(|| 1 + lowest(&bucket[1..], |_| 0))
+    }
+}
+
+/// Like [`DepthPreferred`], except that the quality of an entry is reduced by `weight` each time the table is aged without the
+/// entry being referenced, so that high-quality entries that are no longer relevant are eventually replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AgeWeighted {
+    /// The reduction in quality per unit of age
+    pub weight: i16,
+}
+
+impl AgeWeighted {
+    fn effective_quality(&self, slot: &Slot) -> i16 {
+        slot.quality.saturating_sub(self.weight.saturating_mul(slot.age))
+    }
+}
+
+impl ReplacementPolicy for AgeWeighted {
+    fn victim(&self, bucket: &[Slot], quality: i16) -> Option<usize> {
+        let i = lowest(bucket, |slot| self.effective_quality(slot));
+        (quality >= self.effective_quality(&bucket[i])).then_some(i)
+    }
+
+    fn replaces(&self, bucket: &[Slot], index: usize, quality: i16) -> bool {
+        quality >= self.effective_quality(&bucket[index])
+    }
+}
+
+// Returns the index of the entry with the lowest key. Ties are broken in favor of the oldest entry, then the first.
+fn lowest(bucket: &[Slot], key: impl Fn(&Slot) -> i16) -> usize {
+    let mut best = 0;
+    for (i, slot) in bucket.iter().enumerate().skip(1) {
+        let (k, best_k) = (key(slot), key(&bucket[best]));
+        if k < best_k || (k == best_k && slot.age > bucket[best].age) {
+            best = i;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slots(slots: &[(i16, i16)]) -> Vec<Slot> {
+        slots.iter().map(|&(quality, age)| Slot { quality, age }).collect()
+    }
+
+    #[test]
+    fn test_depth_preferred() {
+        let bucket = slots(&[(3, 0), (1, 0), (1, 2), (4, 5)]);
+        assert_eq!(DepthPreferred.victim(&bucket, 1), Some(2));
+        assert_eq!(DepthPreferred.victim(&bucket, 0), None);
+        assert!(DepthPreferred.replaces(&bucket, 0, 3));
+        assert!(!DepthPreferred.replaces(&bucket, 0, 2));
+    }
+
+    #[test]
+    fn test_always_replace() {
+        let bucket = slots(&[(3, 1), (1, 0), (9, 4), (4, 2)]);
+        assert_eq!(AlwaysReplace.victim(&bucket, 0), Some(2));
+        assert!(AlwaysReplace.replaces(&bucket, 2, 0));
+    }
+
+    #[test]
+    fn test_two_tier() {
+        let bucket = slots(&[(5, 0), (9, 0), (1, 3)]);
+        assert_eq!(TwoTier.victim(&bucket, 5), Some(0));
+        assert_eq!(TwoTier.victim(&bucket, 4), Some(2));
+        assert!(!TwoTier.replaces(&bucket, 0, 4));
+        assert!(TwoTier.replaces(&bucket, 1, 0));
+
+        // The replaced depth-preferred entry is moved to the oldest of the other entries
+        assert_eq!(TwoTier.demote(&bucket, 0), Some(2));
+        assert_eq!(TwoTier.demote(&bucket, 2), None);
+        assert_eq!(DepthPreferred.demote(&bucket, 0), None);
+
+        // With a single entry, it is depth-preferred
+        assert_eq!(TwoTier.victim(&bucket[..1], 4), None);
+        assert_eq!(TwoTier.demote(&bucket[..1], 0), None);
+    }
+
+    #[test]
+    fn test_age_weighted() {
+        let policy = AgeWeighted { weight: 2 };
+
+        // The effective qualities are 5, 2 and 3
+        let bucket = slots(&[(5, 0), (8, 3), (3, 0)]);
+        assert_eq!(policy.victim(&bucket, 2), Some(1));
+        assert_eq!(policy.victim(&bucket, 1), None);
+        assert!(policy.replaces(&bucket, 1, 2));
+        assert!(!policy.replaces(&bucket, 0, 4));
+        assert!(!policy.expired(&bucket[1], 3));
+    }
+}
@@ -0,0 +1,219 @@
+//! Unit tests for the opening book implementation
+//!
+//! These tests use a simple counting game to verify building, saving, loading and consulting a book.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use game_player::book::{self, Book, Candidate};
+use game_player::minimax::{self, ResponseGenerator};
+use game_player::state::{PlayerId, State};
+use game_player::static_evaluator::StaticEvaluator;
+use game_player::transposition_table::TranspositionTable;
+
+/// A counting game. The players take turns adding 1 or 2 to the total, and the game ends when the total reaches 10.
+#[derive(Debug, Clone, PartialEq)]
+struct CountState {
+    total: u32,
+    player: u8,
+}
+
+impl CountState {
+    fn new() -> Self {
+        Self {
+            total: 0,
+            player: PlayerId::ALICE as u8,
+        }
+    }
+}
+
+impl State for CountState {
+    type Action = u32;
+
+    fn fingerprint(&self) -> u64 {
+        (self.total as u64) << 1 | self.player as u64
+    }
+
+    fn whose_turn(&self) -> u8 {
+        self.player
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.total >= 10
+    }
+
+    fn apply(&self, added: &u32) -> Self {
+        Self {
+            total: self.total + added,
+            player: 1 - self.player,
+        }
+    }
+}
+
+struct CountGame;
+
+impl StaticEvaluator<CountState> for CountGame {
+    fn evaluate(&self, state: &CountState) -> f32 {
+        state.total as f32
+    }
+
+    fn alice_wins_value(&self) -> f32 {
+        100.0
+    }
+
+    fn bob_wins_value(&self) -> f32 {
+        -100.0
+    }
+}
+
+impl ResponseGenerator for CountGame {
+    type State = CountState;
+
+    fn generate(&self, state: &Rc<CountState>, _depth: i32) -> Vec<Box<CountState>> {
+        if state.is_terminal() {
+            return Vec::new();
+        }
+        [1, 2].iter().map(|added| Box::new(state.apply(added))).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(total: u32, player: PlayerId) -> u64 {
+        CountState {
+            total,
+            player: player as u8,
+        }
+        .fingerprint()
+    }
+
+    #[test]
+    fn test_add_game() {
+        let s0 = CountState::new();
+        let mut book = Book::new();
+        book.add_game(&s0, &[1, 2, 1], 10);
+        book.add_game(&s0, &[1, 1], 10);
+        book.add_game(&s0, &[2], 10);
+
+        assert_eq!(book.len(), 3);
+        assert_eq!(
+            book.candidates(s0.fingerprint()),
+            &[
+                Candidate {
+                    response: fingerprint(1, PlayerId::BOB),
+                    weight: 2
+                },
+                Candidate {
+                    response: fingerprint(2, PlayerId::BOB),
+                    weight: 1
+                },
+            ]
+        );
+        assert_eq!(book.candidates(fingerprint(1, PlayerId::BOB)).len(), 2);
+        assert_eq!(book.candidates(fingerprint(3, PlayerId::ALICE)).len(), 1);
+        assert!(book.candidates(fingerprint(4, PlayerId::BOB)).is_empty());
+    }
+
+    #[test]
+    fn test_add_game_max_plies() {
+        let s0 = CountState::new();
+        let mut book = Book::new();
+        book.add_game(&s0, &[1, 2, 1, 2], 2);
+
+        assert_eq!(book.len(), 2);
+        assert!(book.candidates(fingerprint(3, PlayerId::ALICE)).is_empty());
+    }
+
+    #[test]
+    fn test_choose_in_proportion_to_weights() {
+        let mut book = Book::new();
+        book.add(1, 10, 1);
+        book.add(1, 20, 2);
+        book.add(1, 30, 1);
+        book.add(2, 10, 0);
+
+        assert_eq!(book.choose(1, 0.0), Some(10));
+        assert_eq!(book.choose(1, 0.24), Some(10));
+        assert_eq!(book.choose(1, 0.25), Some(20));
+        assert_eq!(book.choose(1, 0.74), Some(20));
+        assert_eq!(book.choose(1, 0.75), Some(30));
+        assert_eq!(book.choose(1, 1.0), Some(30));
+
+        // A state with no weight, or not in the book
+        assert_eq!(book.choose(2, 0.5), None);
+        assert_eq!(book.choose(3, 0.5), None);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let s0 = CountState::new();
+        let mut book = Book::new();
+        book.add_game(&s0, &[1, 2, 1], 10);
+        book.add_game(&s0, &[2, 2], 10);
+        let path = std::env::temp_dir().join(format!("game_player_book_{}.book", std::process::id()));
+
+        book.save(&path).unwrap();
+        let loaded = Book::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), book);
+
+        // The header is 16 bytes, each state is 12 bytes, and each candidate is 12 bytes
+        let mut bytes = Vec::new();
+        book.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 16 + 4 * 12 + 5 * 12);
+    }
+
+    #[test]
+    fn test_load_rejects_invalid_data() {
+        let mut book = Book::new();
+        book.add(1, 2, 3);
+        let mut bytes = Vec::new();
+        book.write_to(&mut bytes).unwrap();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(Book::read_from(&mut bad_magic.as_slice()).is_err());
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 99;
+        assert!(Book::read_from(&mut bad_version.as_slice()).is_err());
+
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(Book::read_from(&mut &truncated[..]).is_err());
+    }
+
+    #[test]
+    fn test_search_uses_book() {
+        let s0 = Rc::new(CountState::new());
+        let mut book = Book::new();
+        book.add_game(&*s0, &[1], 1);
+        book.add_game(&*s0, &[2], 1);
+
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let response = book::search(&book, &tt, &CountGame, &CountGame, &s0, 4, 0.2).unwrap();
+        assert_eq!(response.total, 1);
+        let response = book::search(&book, &tt, &CountGame, &CountGame, &s0, 4, 0.7).unwrap();
+        assert_eq!(response.total, 2);
+    }
+
+    #[test]
+    fn test_search_falls_back_to_minimax() {
+        let s0 = Rc::new(CountState::new());
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let expected = minimax::search(&tt, &CountGame, &CountGame, &s0, 4).unwrap();
+
+        // The state is not in the book
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let response = book::search(&Book::new(), &tt, &CountGame, &CountGame, &s0, 4, 0.5).unwrap();
+        assert_eq!(response, expected);
+
+        // The book's response is not a valid response
+        let mut book = Book::new();
+        book.add(s0.fingerprint(), fingerprint(3, PlayerId::BOB), 1);
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let response = book::search(&book, &tt, &CountGame, &CountGame, &s0, 4, 0.5).unwrap();
+        assert_eq!(response, expected);
+    }
+}
@@ -0,0 +1,318 @@
+//! Unit tests for the expectiminimax search implementation
+//!
+//! These tests use a small dice game to verify the search against a brute-force expectiminimax evaluation.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use game_player::expectiminimax::{
+    ChancePruning, ChanceResponseGenerator, ChanceState, Config, Outcome, search, search_with_config,
+};
+use game_player::minimax::ResponseGenerator;
+use game_player::state::{PlayerId, State};
+use game_player::static_evaluator::StaticEvaluator;
+use game_player::transposition_table::TranspositionTable;
+
+/// A small dice game. On each turn, the player to move either holds and scores 1 point, or bets 1 or 2 and rolls a die. A
+/// bet of 1 rolls a 4-sided die and scores the roll unless it is 1, which loses 2 points. A bet of 2 rolls a 6-sided die and
+/// scores the roll unless it is 1 or 2, which loses 3 points. The player with the most points after the last turn wins.
+#[derive(Debug, Clone, PartialEq)]
+struct DiceState {
+    alice: i32,
+    bob: i32,
+    player: u8,
+    bet: Option<u8>, // The bet being rolled for. Some indicates a chance node.
+    turns_left: u8,
+}
+
+impl DiceState {
+    fn new(player: u8, turns_left: u8) -> Self {
+        Self {
+            alice: 0,
+            bob: 0,
+            player,
+            bet: None,
+            turns_left,
+        }
+    }
+
+    // Returns the state after the player to move scores the given number of points
+    fn score(&self, points: i32) -> Self {
+        let mut next = self.clone();
+        if self.player == PlayerId::ALICE as u8 {
+            next.alice += points;
+        } else {
+            next.bob += points;
+        }
+        next.player = 1 - self.player;
+        next.bet = None;
+        next.turns_left -= 1;
+        next
+    }
+
+    // Returns the possible responses by the player to move
+    fn responses(&self) -> Vec<DiceState> {
+        if self.is_terminal() || self.bet.is_some() {
+            return Vec::new();
+        }
+        let mut responses = vec![self.score(1)];
+        for bet in 1..=2 {
+            let mut next = self.clone();
+            next.bet = Some(bet);
+            responses.push(next);
+        }
+        responses
+    }
+
+    // Returns the possible outcomes of the roll and their probabilities
+    fn outcomes(&self) -> Vec<(DiceState, f32)> {
+        let (sides, losing, penalty) = match self.bet {
+            Some(1) => (4, 1, 2),
+            Some(2) => (6, 2, 3),
+            _ => return Vec::new(),
+        };
+        (1..=sides)
+            .map(|roll| {
+                let points = if roll <= losing { -penalty } else { roll };
+                (self.score(points), 1.0 / sides as f32)
+            })
+            .collect()
+    }
+}
+
+impl State for DiceState {
+    type Action = u8;
+
+    fn fingerprint(&self) -> u64 {
+        ((self.alice + 128) as u64)
+            | ((self.bob + 128) as u64) << 8
+            | (self.player as u64) << 16
+            | (self.bet.unwrap_or(0) as u64) << 17
+            | (self.turns_left as u64) << 20
+    }
+
+    fn whose_turn(&self) -> u8 {
+        self.player
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.turns_left == 0
+    }
+
+    fn apply(&self, action: &u8) -> Self {
+        let mut next = self.clone();
+        if *action == 0 {
+            next = self.score(1);
+        } else {
+            next.bet = Some(*action);
+        }
+        next
+    }
+}
+
+impl ChanceState for DiceState {
+    fn is_chance_node(&self) -> bool {
+        self.bet.is_some()
+    }
+}
+
+struct DiceEvaluator;
+
+impl StaticEvaluator<DiceState> for DiceEvaluator {
+    // The value is the score difference, limited to the win values. The narrow range of values makes chance node pruning
+    // effective.
+    fn evaluate(&self, state: &DiceState) -> f32 {
+        (state.alice - state.bob).clamp(-6, 6) as f32
+    }
+
+    fn alice_wins_value(&self) -> f32 {
+        6.0
+    }
+
+    fn bob_wins_value(&self) -> f32 {
+        -6.0
+    }
+}
+
+/// Dice game response generator that counts the number of nodes expanded
+#[derive(Default)]
+struct DiceGenerator {
+    expanded: Cell<usize>,
+}
+
+impl ResponseGenerator for DiceGenerator {
+    type State = DiceState;
+
+    fn generate(&self, state: &Rc<DiceState>, _depth: i32) -> Vec<Box<DiceState>> {
+        self.expanded.set(self.expanded.get() + 1);
+        state.responses().into_iter().map(Box::new).collect()
+    }
+}
+
+impl ChanceResponseGenerator for DiceGenerator {
+    fn outcomes(&self, state: &Rc<DiceState>, _depth: i32) -> Vec<Outcome<DiceState>> {
+        self.expanded.set(self.expanded.get() + 1);
+        state
+            .outcomes()
+            .into_iter()
+            .map(|(state, probability)| Outcome {
+                state: Box::new(state),
+                probability,
+            })
+            .collect()
+    }
+}
+
+// Computes the expectiminimax value of a state by brute force
+fn brute_force_value(state: &DiceState, depth: i32, max_depth: i32) -> f32 {
+    let evaluator = DiceEvaluator;
+    let value = evaluator.evaluate(state);
+    if depth >= max_depth || value >= evaluator.alice_wins_value() || value <= evaluator.bob_wins_value() {
+        return value;
+    }
+    if state.is_chance_node() {
+        return state
+            .outcomes()
+            .iter()
+            .map(|(outcome, p)| p * brute_force_value(outcome, depth + 1, max_depth))
+            .sum();
+    }
+    let values = state
+        .responses()
+        .iter()
+        .map(|response| brute_force_value(response, depth + 1, max_depth))
+        .collect::<Vec<_>>();
+    if values.is_empty() {
+        evaluator.evaluate(state)
+    } else if state.player == PlayerId::ALICE as u8 {
+        values.into_iter().fold(f32::NEG_INFINITY, f32::max)
+    } else {
+        values.into_iter().fold(f32::INFINITY, f32::min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1.0e-4;
+
+    #[test]
+    fn test_search_returns_none_for_no_moves() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let state = Rc::new(DiceState::new(PlayerId::ALICE as u8, 0));
+
+        assert!(search(&tt, &DiceEvaluator, &DiceGenerator::default(), &state, 4).is_none());
+    }
+
+    #[test]
+    fn test_search_returns_none_for_chance_node() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let mut state = DiceState::new(PlayerId::ALICE as u8, 2);
+        state.bet = Some(1);
+
+        assert!(search(&tt, &DiceEvaluator, &DiceGenerator::default(), &Rc::new(state), 4).is_none());
+    }
+
+    #[test]
+    fn test_chance_node_expected_value() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let state = Rc::new(DiceState::new(PlayerId::ALICE as u8, 1));
+
+        // With one turn left, the expected values are: hold 1.0, bet 1 1.75, bet 2 2.0
+        let result = search(&tt, &DiceEvaluator, &DiceGenerator::default(), &state, 2).unwrap();
+        assert_eq!(result.bet, Some(2));
+
+        let (value, _) = tt.borrow_mut().check(state.fingerprint(), -1).unwrap();
+        assert!((value - 2.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_horizon_prefers_certain_outcome() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let state = Rc::new(DiceState::new(PlayerId::ALICE as u8, 1));
+
+        // With a depth of 1, the rolls are not searched and holding is the only response that scores
+        let result = search(&tt, &DiceEvaluator, &DiceGenerator::default(), &state, 1).unwrap();
+        assert_eq!(result.bet, None);
+        assert_eq!(result.alice, 1);
+    }
+
+    #[test]
+    fn test_search_matches_brute_force() {
+        for max_depth in 1..=6 {
+            for player in [PlayerId::ALICE as u8, PlayerId::BOB as u8] {
+                let tt = Rc::new(RefCell::new(TranspositionTable::new(10000, 100)));
+                let state = Rc::new(DiceState::new(player, 3));
+
+                let result = search(&tt, &DiceEvaluator, &DiceGenerator::default(), &state, max_depth).unwrap();
+
+                // The value of the chosen response must be the value of the state
+                let expected = brute_force_value(&state, 0, max_depth);
+                let actual = brute_force_value(&result, 1, max_depth);
+                assert!(
+                    (expected - actual).abs() < EPSILON,
+                    "depth {max_depth}, player {player}: expected {expected}, got {actual}"
+                );
+
+                // The value of the state saved in the T-table must be exact
+                let (value, _) = tt.borrow_mut().check(state.fingerprint(), -1).unwrap();
+                assert!((expected - value).abs() < EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    fn test_transposition_table_reuse() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(10000, 100)));
+        let state = Rc::new(DiceState::new(PlayerId::ALICE as u8, 3));
+
+        // A second search using the values saved by the first must find an equally good response
+        let first = search(&tt, &DiceEvaluator, &DiceGenerator::default(), &state, 5).unwrap();
+        let second = search(&tt, &DiceEvaluator, &DiceGenerator::default(), &state, 5).unwrap();
+        let first_value = brute_force_value(&first, 1, 5);
+        let second_value = brute_force_value(&second, 1, 5);
+        assert!((first_value - second_value).abs() < EPSILON);
+    }
+
+    // Searches the dice game with the given pruning and returns the chosen response, the value of the state, and the number of
+    // nodes expanded
+    fn search_dice(state: &Rc<DiceState>, max_depth: i32, chance_pruning: ChancePruning) -> (Rc<DiceState>, f32, usize) {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(10000, 100)));
+        let generator = DiceGenerator::default();
+        let config = Config { chance_pruning };
+
+        let result = search_with_config(&tt, &DiceEvaluator, &generator, state, max_depth, &config).unwrap();
+        let (value, _) = tt.borrow_mut().check(state.fingerprint(), -1).unwrap();
+        (result, value, generator.expanded.get())
+    }
+
+    #[test]
+    fn test_pruned_search_matches_unpruned() {
+        for max_depth in 1..=6 {
+            for player in [PlayerId::ALICE as u8, PlayerId::BOB as u8] {
+                let state = Rc::new(DiceState::new(player, 3));
+                let (_, expected, _) = search_dice(&state, max_depth, ChancePruning::Disabled);
+
+                for pruning in [ChancePruning::Star1, ChancePruning::Star2] {
+                    let (result, value, _) = search_dice(&state, max_depth, pruning);
+                    assert!(
+                        (expected - value).abs() < EPSILON,
+                        "{pruning:?}, depth {max_depth}, player {player}: expected {expected}, got {value}"
+                    );
+                    let actual = brute_force_value(&result, 1, max_depth);
+                    assert!((expected - actual).abs() < EPSILON);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_star1_expands_fewer_nodes() {
+        let state = Rc::new(DiceState::new(PlayerId::ALICE as u8, 4));
+        let (_, _, unpruned) = search_dice(&state, 8, ChancePruning::Disabled);
+        let (_, _, star1) = search_dice(&state, 8, ChancePruning::Star1);
+
+        assert!(star1 < unpruned, "Star1 expanded {star1} nodes, unpruned expanded {unpruned}");
+    }
+}
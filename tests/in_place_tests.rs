@@ -0,0 +1,243 @@
+//! Unit tests for the make/unmake minimax search implementation
+//!
+//! These tests use a small game to verify that the make/unmake search agrees with the minimax search.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use game_player::minimax::in_place::{ActionGenerator, UndoableState, search, search_with_config};
+use game_player::minimax::probcut::ProbCut;
+use game_player::minimax::{self, Config, InternalIterativeDeepening, ResponseGenerator, SingularExtensions};
+use game_player::state::{PlayerId, State};
+use game_player::static_evaluator::StaticEvaluator;
+use game_player::transposition_table::TranspositionTable;
+
+const CARDS: [i32; 8] = [3, 9, 1, 2, 7, 5, 8, 4];
+
+/// Actions of the card game: take the card on the left or on the right end of the row
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Take {
+    Left,
+    Right,
+}
+
+/// A card game. The players take turns taking a card from either end of a row of cards. The value of a card is added to the
+/// score of the player taking it. The player with the highest score at the end wins.
+#[derive(Debug, Clone, PartialEq)]
+struct CardState {
+    left: usize,  // Index of the leftmost remaining card
+    right: usize, // Index after the rightmost remaining card
+    scores: [i32; 2],
+    player: u8,
+}
+
+impl CardState {
+    fn new() -> Self {
+        Self {
+            left: 0,
+            right: CARDS.len(),
+            scores: [0, 0],
+            player: PlayerId::ALICE as u8,
+        }
+    }
+
+    fn actions(&self) -> Vec<Take> {
+        if self.is_terminal() {
+            Vec::new()
+        } else {
+            vec![Take::Left, Take::Right]
+        }
+    }
+}
+
+impl State for CardState {
+    type Action = Take;
+
+    fn fingerprint(&self) -> u64 {
+        (self.left as u64) | (self.right as u64) << 8 | (self.scores[0] as u64) << 16 | (self.player as u64) << 32
+    }
+
+    fn whose_turn(&self) -> u8 {
+        self.player
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.left == self.right
+    }
+
+    fn apply(&self, action: &Take) -> Self {
+        let mut next = self.clone();
+        next.apply_in_place(action);
+        next
+    }
+}
+
+impl UndoableState for CardState {
+    fn apply_in_place(&mut self, action: &Take) {
+        let card = match action {
+            Take::Left => {
+                self.left += 1;
+                CARDS[self.left - 1]
+            }
+            Take::Right => {
+                self.right -= 1;
+                CARDS[self.right]
+            }
+        };
+        self.scores[self.player as usize] += card;
+        self.player = 1 - self.player;
+    }
+
+    fn undo(&mut self, action: &Take) {
+        self.player = 1 - self.player;
+        let card = match action {
+            Take::Left => {
+                self.left -= 1;
+                CARDS[self.left]
+            }
+            Take::Right => {
+                self.right += 1;
+                CARDS[self.right - 1]
+            }
+        };
+        self.scores[self.player as usize] -= card;
+    }
+}
+
+struct CardEvaluator;
+
+impl StaticEvaluator<CardState> for CardEvaluator {
+    fn evaluate(&self, state: &CardState) -> f32 {
+        (state.scores[0] - state.scores[1]) as f32
+    }
+
+    fn alice_wins_value(&self) -> f32 {
+        100.0
+    }
+
+    fn bob_wins_value(&self) -> f32 {
+        -100.0
+    }
+}
+
+/// Generates actions for the make/unmake search and states for the minimax search
+struct CardGenerator;
+
+impl ActionGenerator for CardGenerator {
+    type State = CardState;
+
+    fn generate(&self, state: &CardState, _depth: i32) -> Vec<Take> {
+        state.actions()
+    }
+}
+
+impl ResponseGenerator for CardGenerator {
+    type State = CardState;
+
+    fn generate(&self, state: &Rc<CardState>, _depth: i32) -> Vec<Box<CardState>> {
+        state.actions().iter().map(|action| Box::new(state.apply(action))).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_actions() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let mut state = CardState::new();
+        state.left = state.right;
+
+        assert!(search(&tt, &CardEvaluator, &CardGenerator, &mut state, 4).is_none());
+    }
+
+    #[test]
+    fn test_undo_restores_state() {
+        let mut state = CardState::new();
+        let original = state.clone();
+        for action in [Take::Left, Take::Right, Take::Right] {
+            state.apply_in_place(&action);
+        }
+        for action in [Take::Right, Take::Right, Take::Left] {
+            state.undo(&action);
+        }
+        assert_eq!(state, original);
+    }
+
+    #[test]
+    fn test_search_restores_state() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let mut state = CardState::new();
+
+        assert!(search(&tt, &CardEvaluator, &CardGenerator, &mut state, 6).is_some());
+        assert_eq!(state, CardState::new());
+    }
+
+    #[test]
+    fn test_matches_minimax_search() {
+        for max_depth in 1..=CARDS.len() as i32 {
+            for player in [PlayerId::ALICE as u8, PlayerId::BOB as u8] {
+                let mut state = CardState::new();
+                state.player = player;
+
+                let tt = Rc::new(RefCell::new(TranspositionTable::new(10000, 100)));
+                let action = search(&tt, &CardEvaluator, &CardGenerator, &mut state, max_depth).unwrap();
+
+                let minimax_tt = Rc::new(RefCell::new(TranspositionTable::new(10000, 100)));
+                let root = Rc::new(state.clone());
+                let response = minimax::search(&minimax_tt, &CardEvaluator, &CardGenerator, &root, max_depth).unwrap();
+
+                // Both searches choose the same response and find the same value for the state
+                assert_eq!(*response, state.apply(&action), "depth {max_depth}, player {player}");
+                let fingerprint = state.fingerprint();
+                assert_eq!(tt.borrow_mut().check(fingerprint, -1), minimax_tt.borrow_mut().check(fingerprint, -1));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "singular extensions are not supported")]
+    fn test_singular_extensions_are_rejected() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let config = Config {
+            singular: Some(SingularExtensions {
+                margin: 1.0,
+                min_depth: 2,
+                reduction: 1,
+                max_extensions: 2,
+            }),
+            ..Config::default()
+        };
+        search_with_config(&tt, &CardEvaluator, &CardGenerator, &mut CardState::new(), 4, &config);
+    }
+
+    #[test]
+    #[should_panic(expected = "ProbCut is not supported")]
+    fn test_probcut_is_rejected() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let config = Config {
+            probcut: Some(ProbCut {
+                depth: 4,
+                shallow_depth: 2,
+                slope: 1.0,
+                intercept: 0.0,
+                sigma: 1.0,
+                threshold: 1.5,
+            }),
+            ..Config::default()
+        };
+        search_with_config(&tt, &CardEvaluator, &CardGenerator, &mut CardState::new(), 4, &config);
+    }
+
+    #[test]
+    #[should_panic(expected = "internal iterative deepening is not supported")]
+    fn test_internal_iterative_deepening_is_rejected() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let config = Config {
+            iid: Some(InternalIterativeDeepening::default()),
+            ..Config::default()
+        };
+        search_with_config(&tt, &CardEvaluator, &CardGenerator, &mut CardState::new(), 4, &config);
+    }
+}
@@ -7,10 +7,16 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 
-use game_player::minimax::{ResponseGenerator, search};
+use game_player::minimax::probcut::{self, ProbCut};
+use game_player::minimax::{
+    Config, InternalIterativeDeepening, ResponseGenerator, SingularExtensions, Statistics, search, search_with_config,
+    search_with_statistics,
+};
+use game_player::pns::GameResult;
+use game_player::retrograde::Solution;
 use game_player::state::{PlayerId, State};
-use game_player::static_evaluator::StaticEvaluator;
-use game_player::transposition_table::TranspositionTable;
+use game_player::static_evaluator::{Oracle, StaticEvaluator};
+use game_player::transposition_table::{TableError, TranspositionTable};
 
 /// Mock action type for testing
 #[derive(Debug, Clone, PartialEq)]
@@ -25,6 +31,7 @@ struct MockGameState {
     player: u8,
     value: Option<f32>, // Pre-set value for leaf nodes
     children: Vec<u32>, // IDs of child states
+    draw: bool,         // True if the game ended in a draw
 }
 
 impl MockGameState {
@@ -34,6 +41,7 @@ impl MockGameState {
             player,
             value: None,
             children: Vec::new(),
+            draw: false,
         }
     }
 
@@ -46,6 +54,11 @@ impl MockGameState {
         self.children = children;
         self
     }
+
+    fn with_draw(mut self) -> Self {
+        self.draw = true;
+        self
+    }
 }
 
 impl State for MockGameState {
@@ -60,7 +73,11 @@ impl State for MockGameState {
     }
 
     fn is_terminal(&self) -> bool {
-        self.children.is_empty() && self.value.is_some()
+        self.children.is_empty() && (self.value.is_some() || self.draw)
+    }
+
+    fn is_draw(&self) -> bool {
+        self.draw
     }
 
     fn apply(&self, _action: &MockAction) -> Self {
@@ -73,12 +90,14 @@ impl State for MockGameState {
 /// Mock static evaluator that returns pre-set values or defaults
 struct MockStaticEvaluator {
     values: HashMap<u32, f32>,
+    repetition: Option<f32>,
 }
 
 impl MockStaticEvaluator {
     fn new() -> Self {
         Self {
             values: HashMap::new(),
+            repetition: None,
         }
     }
 
@@ -86,6 +105,11 @@ impl MockStaticEvaluator {
         self.values.insert(state_id, value);
         self
     }
+
+    fn with_repetition_value(mut self, value: f32) -> Self {
+        self.repetition = Some(value);
+        self
+    }
 }
 
 impl StaticEvaluator<MockGameState> for MockStaticEvaluator {
@@ -102,6 +126,58 @@ impl StaticEvaluator<MockGameState> for MockStaticEvaluator {
     fn bob_wins_value(&self) -> f32 {
         -1000.0
     }
+
+    fn repetition_value(&self, _state: &MockGameState) -> Option<f32> {
+        self.repetition
+    }
+}
+
+/// Evaluator with integer values, which are the mock evaluator's values scaled by 10
+struct IntegerEvaluator(MockStaticEvaluator);
+
+impl StaticEvaluator<MockGameState, i16> for IntegerEvaluator {
+    fn evaluate(&self, state: &MockGameState) -> i16 {
+        (self.0.evaluate(state) * 10.0) as i16
+    }
+
+    fn alice_wins_value(&self) -> i16 {
+        10000
+    }
+
+    fn bob_wins_value(&self) -> i16 {
+        -10000
+    }
+}
+
+/// Evaluator with an oracle that knows the results of some states
+struct OracleEvaluator {
+    inner: MockStaticEvaluator,
+    solutions: HashMap<u32, Solution>,
+    win_value: f32,
+}
+
+impl StaticEvaluator<MockGameState> for OracleEvaluator {
+    fn evaluate(&self, state: &MockGameState) -> f32 {
+        self.inner.evaluate(state)
+    }
+
+    fn alice_wins_value(&self) -> f32 {
+        self.win_value
+    }
+
+    fn bob_wins_value(&self) -> f32 {
+        -self.win_value
+    }
+
+    fn oracle(&self) -> Option<&dyn Oracle<MockGameState>> {
+        Some(self)
+    }
+}
+
+impl Oracle<MockGameState> for OracleEvaluator {
+    fn probe(&self, state: &MockGameState) -> Option<Solution> {
+        self.solutions.get(&state.id).copied()
+    }
 }
 
 /// Mock response generator that creates predefined child states
@@ -137,6 +213,75 @@ impl ResponseGenerator for MockResponseGenerator {
     }
 }
 
+/// Mock response generator that generates one child per stage and records the children generated
+struct StagedResponseGenerator {
+    inner: MockResponseGenerator,
+    generated: RefCell<Vec<u32>>,
+}
+
+impl StagedResponseGenerator {
+    fn new(inner: MockResponseGenerator) -> Self {
+        Self {
+            inner,
+            generated: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl ResponseGenerator for StagedResponseGenerator {
+    type State = MockGameState;
+    fn generate(&self, state: &Rc<MockGameState>, depth: i32) -> Vec<Box<MockGameState>> {
+        self.generate_staged(state, depth).flatten().collect()
+    }
+
+    fn generate_staged(&self, state: &Rc<MockGameState>, _depth: i32) -> impl Iterator<Item = Vec<Box<MockGameState>>> {
+        state.children.clone().into_iter().map(|child_id| {
+            self.generated.borrow_mut().push(child_id);
+            vec![Box::new(self.inner.states[&child_id].clone())]
+        })
+    }
+}
+
+/// Response generator for a uniform tree in which the value of each state is its exact minimax value, so a shallow search
+/// predicts a deep search exactly. The children of state n are n * branching + i. The value of child 0 is the value of its
+/// parent, and the values of the others are worse for the player to move.
+struct ExactTreeGenerator {
+    branching: u32,
+}
+
+impl ResponseGenerator for ExactTreeGenerator {
+    type State = MockGameState;
+    fn generate(&self, state: &Rc<MockGameState>, _depth: i32) -> Vec<Box<MockGameState>> {
+        let parent = state.value.unwrap_or(0.0);
+        (0..self.branching)
+            .map(|i| {
+                let id = state.id * self.branching + i;
+                let offset = if i == 0 { 0.0 } else { ((id.wrapping_mul(2654435761) >> 16) % 3 + i * 3) as f32 };
+                let value = if state.player == PlayerId::ALICE as u8 { parent - offset } else { parent + offset };
+                Box::new(MockGameState::new(id, 1 - state.player).with_value(value))
+            })
+            .collect()
+    }
+}
+
+/// Response generator for a uniform tree with pseudo-random values. The children of state n are n * branching + i.
+struct UniformTreeGenerator {
+    branching: u32,
+}
+
+impl ResponseGenerator for UniformTreeGenerator {
+    type State = MockGameState;
+    fn generate(&self, state: &Rc<MockGameState>, _depth: i32) -> Vec<Box<MockGameState>> {
+        (0..self.branching)
+            .map(|i| {
+                let id = state.id * self.branching + i;
+                let value = (id.wrapping_mul(2654435761) >> 16) % 201;
+                Box::new(MockGameState::new(id, 1 - state.player).with_value(value as f32 / 10.0 - 10.0))
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -383,4 +528,715 @@ mod tests {
         let best_move = result.unwrap();
         assert_eq!(best_move.id, 3);
     }
+
+    #[test]
+    fn test_draw_value_without_contempt() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let evaluator = MockStaticEvaluator::new();
+
+        let generator = MockResponseGenerator::new()
+            .add_state(MockGameState::new(2, PlayerId::BOB as u8).with_draw())
+            .add_state(MockGameState::new(3, PlayerId::BOB as u8).with_value(-0.5));
+
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+
+        let result = search(&tt, &evaluator, &generator, &state, 1);
+
+        // The draw (0.0) is better for Alice than -0.5
+        assert_eq!(result.unwrap().id, 2);
+    }
+
+    #[test]
+    fn test_contempt_alice_avoids_draw() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let evaluator = MockStaticEvaluator::new();
+
+        let generator = MockResponseGenerator::new()
+            .add_state(MockGameState::new(2, PlayerId::BOB as u8).with_draw())
+            .add_state(MockGameState::new(3, PlayerId::BOB as u8).with_value(-0.5));
+
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+
+        let config = Config {
+            contempt: 1.0,
+            ..Config::default()
+        };
+        let result = search_with_config(&tt, &evaluator, &generator, &state, 1, &config);
+
+        // With contempt, the draw is worth -1.0 to Alice, so she prefers -0.5
+        assert_eq!(result.unwrap().id, 3);
+    }
+
+    #[test]
+    fn test_contempt_bob_avoids_draw() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let evaluator = MockStaticEvaluator::new();
+
+        let generator = MockResponseGenerator::new()
+            .add_state(MockGameState::new(2, PlayerId::ALICE as u8).with_draw())
+            .add_state(MockGameState::new(3, PlayerId::ALICE as u8).with_value(0.5));
+
+        let state = Rc::new(MockGameState::new(1, PlayerId::BOB as u8).with_children(vec![2, 3]));
+
+        // Without contempt, Bob prefers the draw
+        let result = search(&tt, &evaluator, &generator, &state, 1);
+        assert_eq!(result.unwrap().id, 2);
+
+        // With contempt, the draw is worth +1.0 to Bob, so he prefers 0.5
+        let config = Config {
+            contempt: 1.0,
+            ..Config::default()
+        };
+        let result = search_with_config(&tt, &evaluator, &generator, &state, 1, &config);
+        assert_eq!(result.unwrap().id, 3);
+    }
+
+    #[test]
+    fn test_negative_contempt_seeks_draw() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let evaluator = MockStaticEvaluator::new();
+
+        let generator = MockResponseGenerator::new()
+            .add_state(MockGameState::new(2, PlayerId::BOB as u8).with_draw())
+            .add_state(MockGameState::new(3, PlayerId::BOB as u8).with_value(0.5));
+
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+
+        let config = Config {
+            contempt: -1.0,
+            ..Config::default()
+        };
+        let result = search_with_config(&tt, &evaluator, &generator, &state, 1, &config);
+
+        // With negative contempt, the draw is worth 1.0 to Alice
+        assert_eq!(result.unwrap().id, 2);
+    }
+
+    #[test]
+    fn test_integer_contempt() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::<i16>::new(1000, 100)));
+        let evaluator = IntegerEvaluator(MockStaticEvaluator::new());
+
+        let generator = MockResponseGenerator::new()
+            .add_state(MockGameState::new(2, PlayerId::BOB as u8).with_draw())
+            .add_state(MockGameState::new(3, PlayerId::BOB as u8).with_value(-0.5));
+
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+
+        // With a contempt of 10, the draw is worth -10 to Alice, so she prefers -5
+        let config = Config {
+            contempt: 10,
+            ..Config::default()
+        };
+        let result = search_with_config(&tt, &evaluator, &generator, &state, 1, &config);
+        assert_eq!(result.unwrap().id, 3);
+    }
+
+    #[test]
+    fn test_contempt_draws_are_not_stored() {
+        let evaluator = MockStaticEvaluator::new();
+        let generator = MockResponseGenerator::new()
+            .add_state(MockGameState::new(2, PlayerId::BOB as u8).with_children(vec![4, 5]))
+            .add_state(MockGameState::new(3, PlayerId::BOB as u8).with_value(-2.0))
+            .add_state(MockGameState::new(4, PlayerId::ALICE as u8).with_draw())
+            .add_state(MockGameState::new(5, PlayerId::ALICE as u8).with_value(0.5));
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+
+        // Without contempt, the value of a draw is the same for both players, so it is stored
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        assert_eq!(search(&tt, &evaluator, &generator, &state, 2).unwrap().id, 2);
+        assert_eq!(tt.borrow_mut().check(2, -1), Some((0.0, 1)));
+        assert_eq!(tt.borrow_mut().check(1, -1), Some((0.0, 2)));
+
+        // With contempt, the draw is worth -1.0 to Alice, and the values found by searching 2 and 1 are not stored
+        let config = Config {
+            contempt: 1.0,
+            ..Config::default()
+        };
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        assert_eq!(search_with_config(&tt, &evaluator, &generator, &state, 2, &config).unwrap().id, 2);
+        assert_eq!(tt.borrow_mut().check(2, 1), None);
+        assert_eq!(tt.borrow_mut().check(1, -1), None);
+    }
+
+    // Creates a game in which Alice can either move to a state from which Bob can only return to the start, or move to a
+    // state with a value of -0.5.
+    fn cycle_generator() -> MockResponseGenerator {
+        MockResponseGenerator::new()
+            .add_state(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]))
+            .add_state(MockGameState::new(2, PlayerId::BOB as u8).with_children(vec![1]))
+            .add_state(MockGameState::new(3, PlayerId::BOB as u8).with_value(-0.5))
+    }
+
+    #[test]
+    fn test_repetition_scored_as_draw() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let evaluator = MockStaticEvaluator::new().with_value(2, -10.0);
+        let generator = cycle_generator();
+
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+
+        // Bob's only response to 2 repeats the root, so 2 is worth a draw (0.0), which is better than -0.5
+        let result = search(&tt, &evaluator, &generator, &state, 4);
+        assert_eq!(result.unwrap().id, 2);
+    }
+
+    #[test]
+    fn test_repetition_with_contempt() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let evaluator = MockStaticEvaluator::new();
+        let generator = cycle_generator();
+
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+
+        // With contempt, the repetition is worth -1.0 to Alice
+        let config = Config {
+            contempt: 1.0,
+            ..Config::default()
+        };
+        let result = search_with_config(&tt, &evaluator, &generator, &state, 4, &config);
+        assert_eq!(result.unwrap().id, 3);
+    }
+
+    #[test]
+    fn test_repetition_value_hook() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let evaluator = MockStaticEvaluator::new().with_repetition_value(-5.0);
+        let generator = cycle_generator();
+
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+
+        // The evaluator scores the repetition at -5.0, so Alice avoids it
+        let result = search(&tt, &evaluator, &generator, &state, 4);
+        assert_eq!(result.unwrap().id, 3);
+    }
+
+    #[test]
+    fn test_repetition_of_game_history() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let evaluator = MockStaticEvaluator::new();
+
+        let generator = MockResponseGenerator::new()
+            .add_state(MockGameState::new(2, PlayerId::BOB as u8).with_value(5.0))
+            .add_state(MockGameState::new(3, PlayerId::BOB as u8).with_value(1.0));
+
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+
+        // Without history, 2 is the best move
+        let result = search(&tt, &evaluator, &generator, &state, 1);
+        assert_eq!(result.unwrap().id, 2);
+
+        // 2 occurred earlier in the game, so it is a repetition worth a draw (0.0)
+        let config = Config {
+            history: vec![2],
+            ..Config::default()
+        };
+        let result = search_with_config(&tt, &evaluator, &generator, &state, 1, &config);
+        assert_eq!(result.unwrap().id, 3);
+    }
+
+    #[test]
+    fn test_path_dependent_values_not_saved() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let evaluator = MockStaticEvaluator::new();
+        let generator = cycle_generator();
+
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+
+        let result = search(&tt, &evaluator, &generator, &state, 4);
+        assert!(result.is_some());
+
+        // The values of 1 and 2 depend on the repetition, so no searched values were saved for them
+        let mut tt_borrowed = tt.borrow_mut();
+        assert_eq!(tt_borrowed.check(1, 1), None);
+        assert_eq!(tt_borrowed.check(2, 1), None);
+    }
+
+    #[test]
+    fn test_integer_values() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::<i16>::new(1000, 100)));
+        let evaluator = IntegerEvaluator(MockStaticEvaluator::new());
+
+        // Alice chooses between 2 and 3, then Bob chooses the lowest leaf
+        let generator = MockResponseGenerator::new()
+            .add_state(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]))
+            .add_state(MockGameState::new(2, PlayerId::BOB as u8).with_children(vec![4, 5]))
+            .add_state(MockGameState::new(3, PlayerId::BOB as u8).with_children(vec![6, 7]))
+            .add_state(MockGameState::new(4, PlayerId::ALICE as u8).with_value(0.3))
+            .add_state(MockGameState::new(5, PlayerId::ALICE as u8).with_value(0.8))
+            .add_state(MockGameState::new(6, PlayerId::ALICE as u8).with_value(0.5))
+            .add_state(MockGameState::new(7, PlayerId::ALICE as u8).with_value(0.9));
+
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+
+        let result = search(&tt, &evaluator, &generator, &state, 2);
+        assert_eq!(result.unwrap().id, 3);
+        assert_eq!(tt.borrow_mut().check(1, -1), Some((5, 2)));
+    }
+
+    #[test]
+    fn test_nan_values() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let evaluator = MockStaticEvaluator::new();
+
+        // The values of all responses are NaN. They are ordered consistently, so a response is still chosen.
+        let generator = MockResponseGenerator::new()
+            .add_state(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]))
+            .add_state(MockGameState::new(2, PlayerId::BOB as u8).with_value(f32::NAN))
+            .add_state(MockGameState::new(3, PlayerId::BOB as u8).with_value(f32::NAN));
+
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+
+        assert!(search(&tt, &evaluator, &generator, &state, 1).is_some());
+    }
+
+    #[test]
+    fn test_staged_generation_stops_after_cutoff() {
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let evaluator = MockStaticEvaluator::new();
+
+        // After Bob's response 4 to 2, Alice's alpha is 5.0. Bob's first response to 3 is worse for Alice, so Bob's second
+        // response to 3 is pruned and never generated.
+        let generator = StagedResponseGenerator::new(
+            MockResponseGenerator::new()
+                .add_state(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]))
+                .add_state(MockGameState::new(2, PlayerId::BOB as u8).with_children(vec![4]))
+                .add_state(MockGameState::new(3, PlayerId::BOB as u8).with_children(vec![5, 6]))
+                .add_state(MockGameState::new(4, PlayerId::ALICE as u8).with_value(5.0))
+                .add_state(MockGameState::new(5, PlayerId::ALICE as u8).with_value(1.0))
+                .add_state(MockGameState::new(6, PlayerId::ALICE as u8).with_value(9.0)),
+        );
+
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+
+        let result = search(&tt, &evaluator, &generator, &state, 2);
+        assert_eq!(result.unwrap().id, 2);
+        assert_eq!(*generator.generated.borrow(), vec![2, 4, 3, 5]);
+    }
+
+    #[test]
+    fn test_internal_iterative_deepening() {
+        let generator = UniformTreeGenerator { branching: 4 };
+        let evaluator = MockStaticEvaluator::new();
+        let iid_config = Config {
+            iid: Some(InternalIterativeDeepening {
+                min_depth: 2,
+                reduction: 1,
+            }),
+            ..Config::default()
+        };
+
+        for max_depth in 1..=6 {
+            let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8));
+
+            let tt = Rc::new(RefCell::new(TranspositionTable::new(100000, 100)));
+            let mut statistics = Statistics::default();
+            let config = Config::default();
+            let result = search_with_statistics(&tt, &evaluator, &generator, &state, max_depth, &config, &mut statistics);
+            assert_eq!(statistics.iid_searches, 0);
+
+            let iid_tt = Rc::new(RefCell::new(TranspositionTable::new(100000, 100)));
+            let mut iid_statistics = Statistics::default();
+            let iid_result =
+                search_with_statistics(&iid_tt, &evaluator, &generator, &state, max_depth, &iid_config, &mut iid_statistics);
+
+            // The reduced-depth searches only change the order of the responses, so the result is the same
+            assert_eq!(result.unwrap().id, iid_result.unwrap().id, "depth {max_depth}");
+            assert_eq!(tt.borrow_mut().check(1, -1), iid_tt.borrow_mut().check(1, -1), "depth {max_depth}");
+            assert_eq!(iid_statistics.iid_searches > 0, max_depth >= 2);
+        }
+    }
+
+    #[test]
+    fn test_statistics_accumulate() {
+        let generator = UniformTreeGenerator { branching: 3 };
+        let evaluator = MockStaticEvaluator::new();
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8));
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let mut statistics = Statistics::default();
+
+        // A search of depth 1 searches the root and evaluates its 3 responses
+        search_with_statistics(&tt, &evaluator, &generator, &state, 1, &Config::default(), &mut statistics);
+        assert_eq!(statistics.nodes, 1);
+        assert_eq!(statistics.evaluations, 3);
+
+        // The second search evaluates only the 9 new states
+        search_with_statistics(&tt, &evaluator, &generator, &state, 2, &Config::default(), &mut statistics);
+        assert!(statistics.nodes > 1);
+        assert_eq!(statistics.evaluations, 12);
+    }
+
+    // Creates a tree in which 2 looks good for Alice at a depth of 2, but Alice's only response to 4 loses badly, which is
+    // found at a depth of 3.
+    fn forced_loss_generator() -> MockResponseGenerator {
+        MockResponseGenerator::new()
+            .add_state(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]))
+            .add_state(MockGameState::new(2, PlayerId::BOB as u8).with_children(vec![4]))
+            .add_state(MockGameState::new(3, PlayerId::BOB as u8).with_children(vec![5]))
+            .add_state(MockGameState::new(4, PlayerId::ALICE as u8).with_children(vec![6]))
+            .add_state(MockGameState::new(5, PlayerId::ALICE as u8).with_value(2.0))
+            .add_state(MockGameState::new(6, PlayerId::BOB as u8).with_value(-50.0))
+    }
+
+    #[test]
+    fn test_singular_extension() {
+        let evaluator = MockStaticEvaluator::new().with_value(4, 10.0);
+
+        let generator = forced_loss_generator();
+
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+        let singular_config = Config {
+            singular: Some(SingularExtensions {
+                margin: 3.0,
+                min_depth: 2,
+                reduction: 1,
+                max_extensions: 1,
+            }),
+            ..Config::default()
+        };
+
+        // Without the extension, a second search at the same depth still chooses 2
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        assert_eq!(search(&tt, &evaluator, &generator, &state, 2).unwrap().id, 2);
+        assert_eq!(search(&tt, &evaluator, &generator, &state, 2).unwrap().id, 2);
+
+        // After the first search, 2 is the candidate and is much better than 3, so it is extended
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let mut statistics = Statistics::default();
+        assert_eq!(search(&tt, &evaluator, &generator, &state, 2).unwrap().id, 2);
+        let result = search_with_statistics(&tt, &evaluator, &generator, &state, 2, &singular_config, &mut statistics);
+        assert_eq!(result.unwrap().id, 3);
+        assert_eq!(statistics.singular_extensions, 1);
+    }
+
+    #[test]
+    fn test_singular_extension_requires_margin() {
+        let evaluator = MockStaticEvaluator::new().with_value(4, 10.0);
+        let generator = forced_loss_generator();
+
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+
+        // 3 is within the margin of 2, so 2 is not singular
+        let config = Config {
+            singular: Some(SingularExtensions {
+                margin: 20.0,
+                min_depth: 2,
+                reduction: 1,
+                max_extensions: 1,
+            }),
+            ..Config::default()
+        };
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let mut statistics = Statistics::default();
+        search(&tt, &evaluator, &generator, &state, 2);
+        let result = search_with_statistics(&tt, &evaluator, &generator, &state, 2, &config, &mut statistics);
+        assert_eq!(result.unwrap().id, 2);
+        assert_eq!(statistics.singular_extensions, 0);
+    }
+
+    #[test]
+    fn test_probcut_calibration() {
+        let generator = UniformTreeGenerator { branching: 3 };
+        let evaluator = MockStaticEvaluator::new();
+        let positions = (2..40).map(|id| Rc::new(MockGameState::new(id, (id % 2) as u8))).collect::<Vec<_>>();
+
+        let model = probcut::calibrate(&evaluator, &generator, &positions, 3, 1, 1.5, 1000).unwrap().unwrap();
+        assert_eq!((model.depth, model.shallow_depth, model.threshold), (3, 1, 1.5));
+        assert!(model.slope.is_finite() && model.intercept.is_finite());
+        assert!(model.sigma >= 0.0);
+
+        // Positions without responses are ignored, so there is nothing to fit
+        let positions = vec![Rc::new(MockGameState::new(1, PlayerId::ALICE as u8))];
+        assert_eq!(probcut::calibrate(&evaluator, &MockResponseGenerator::new(), &positions, 3, 1, 1.5, 1000), Ok(None));
+
+        // The transposition tables must not be empty
+        assert_eq!(probcut::calibrate(&evaluator, &generator, &positions, 3, 1, 1.5, 0), Err(TableError::ZeroSize));
+    }
+
+    #[test]
+    fn test_probcut_wide_margin_never_prunes() {
+        let generator = UniformTreeGenerator { branching: 3 };
+        let evaluator = MockStaticEvaluator::new();
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8));
+        let config = Config {
+            probcut: Some(ProbCut {
+                depth: 3,
+                shallow_depth: 1,
+                slope: 1.0,
+                intercept: 0.0,
+                sigma: 1.0,
+                threshold: 1000.0,
+            }),
+            ..Config::default()
+        };
+
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(100000, 100)));
+        let result = search(&tt, &evaluator, &generator, &state, 5);
+
+        let probcut_tt = Rc::new(RefCell::new(TranspositionTable::new(100000, 100)));
+        let mut statistics = Statistics::default();
+        let probcut_result = search_with_statistics(&probcut_tt, &evaluator, &generator, &state, 5, &config, &mut statistics);
+        assert_eq!(result.unwrap().id, probcut_result.unwrap().id);
+        assert_eq!(tt.borrow_mut().check(1, -1), probcut_tt.borrow_mut().check(1, -1));
+        assert_eq!(statistics.probcut_cutoffs, 0);
+    }
+
+    #[test]
+    fn test_probcut_prunes() {
+        let generator = UniformTreeGenerator { branching: 4 };
+        let evaluator = MockStaticEvaluator::new();
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8));
+
+        // Assume that the shallow search predicts the deep search exactly, so every shallow value outside the window prunes
+        let config = Config {
+            probcut: Some(ProbCut {
+                depth: 3,
+                shallow_depth: 1,
+                slope: 1.0,
+                intercept: 0.0,
+                sigma: 0.0,
+                threshold: 1.0,
+            }),
+            ..Config::default()
+        };
+
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(100000, 100)));
+        let mut statistics = Statistics::default();
+        search_with_statistics(&tt, &evaluator, &generator, &state, 5, &Config::default(), &mut statistics);
+
+        let probcut_tt = Rc::new(RefCell::new(TranspositionTable::new(100000, 100)));
+        let mut probcut_statistics = Statistics::default();
+        let result = search_with_statistics(&probcut_tt, &evaluator, &generator, &state, 5, &config, &mut probcut_statistics);
+        assert!(result.is_some());
+        assert!(probcut_statistics.probcut_cutoffs > 0);
+        assert!(probcut_statistics.nodes < statistics.nodes);
+    }
+
+    #[test]
+    fn test_probcut_exact_model_keeps_value() {
+        let generator = ExactTreeGenerator { branching: 3 };
+        let evaluator = MockStaticEvaluator::new();
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_value(0.0));
+
+        // The model is exact, so pruning with no margin does not change the result
+        let config = Config {
+            probcut: Some(ProbCut {
+                depth: 3,
+                shallow_depth: 1,
+                slope: 1.0,
+                intercept: 0.0,
+                sigma: 0.0,
+                threshold: 1.0,
+            }),
+            ..Config::default()
+        };
+
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(100000, 100)));
+        let mut statistics = Statistics::default();
+        let result = search_with_statistics(&tt, &evaluator, &generator, &state, 5, &Config::default(), &mut statistics);
+
+        let probcut_tt = Rc::new(RefCell::new(TranspositionTable::new(100000, 100)));
+        let mut probcut_statistics = Statistics::default();
+        let probcut_result =
+            search_with_statistics(&probcut_tt, &evaluator, &generator, &state, 5, &config, &mut probcut_statistics);
+        assert!(probcut_statistics.probcut_cutoffs > 0);
+        assert!(probcut_statistics.nodes < statistics.nodes);
+        assert_eq!(result.unwrap().id, probcut_result.unwrap().id);
+        assert_eq!(tt.borrow_mut().check(1, -1).unwrap().0, probcut_tt.borrow_mut().check(1, -1).unwrap().0);
+    }
+
+    #[test]
+    fn test_probcut_probe_values_are_not_stored() {
+        // The probe of 3 with the null window [0, 0] stops searching 6 after 12, so it finds 2 rather than 7 as the value of 3
+        // to the shallow depth. The full search of 3 is then cut off, so it does not replace that value.
+        let generator = MockResponseGenerator::new()
+            .add_state(MockGameState::new(2, PlayerId::BOB as u8).with_value(0.0))
+            .add_state(MockGameState::new(3, PlayerId::BOB as u8).with_children(vec![6]))
+            .add_state(MockGameState::new(6, PlayerId::ALICE as u8).with_children(vec![12, 13]))
+            .add_state(MockGameState::new(12, PlayerId::BOB as u8).with_children(vec![24]))
+            .add_state(MockGameState::new(13, PlayerId::BOB as u8).with_children(vec![26]))
+            .add_state(MockGameState::new(24, PlayerId::ALICE as u8).with_children(vec![48]))
+            .add_state(MockGameState::new(26, PlayerId::ALICE as u8).with_children(vec![52]))
+            .add_state(MockGameState::new(48, PlayerId::BOB as u8).with_value(-5.0))
+            .add_state(MockGameState::new(52, PlayerId::BOB as u8).with_value(-3.0));
+        let evaluator = MockStaticEvaluator::new()
+            .with_value(3, -1.0)
+            .with_value(12, 5.0)
+            .with_value(13, 1.0)
+            .with_value(24, 2.0)
+            .with_value(26, 7.0);
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+        let config = Config {
+            probcut: Some(ProbCut {
+                depth: 4,
+                shallow_depth: 3,
+                slope: 1.0,
+                intercept: 0.0,
+                sigma: 0.0,
+                threshold: 1.0,
+            }),
+            ..Config::default()
+        };
+
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let result = search_with_config(&tt, &evaluator, &generator, &state, 5, &config);
+        assert_eq!(result.unwrap().id, 2);
+        assert_eq!(tt.borrow_mut().check(3, -1), Some((-1.0, 0)));
+        assert_eq!(tt.borrow_mut().check(12, -1), Some((5.0, 0)));
+    }
+
+    #[test]
+    fn test_oracle_prefers_quicker_win() {
+        // The responses to 2 and 3 lose for Alice, but they are never searched because the oracle knows the results
+        let generator = MockResponseGenerator::new()
+            .add_state(MockGameState::new(2, PlayerId::BOB as u8).with_children(vec![5]))
+            .add_state(MockGameState::new(3, PlayerId::BOB as u8).with_children(vec![5]))
+            .add_state(MockGameState::new(4, PlayerId::BOB as u8).with_value(500.0))
+            .add_state(MockGameState::new(5, PlayerId::ALICE as u8).with_value(-1000.0));
+        let evaluator = OracleEvaluator {
+            inner: MockStaticEvaluator::new(),
+            solutions: HashMap::from([
+                (
+                    2,
+                    Solution {
+                        result: GameResult::AliceWins,
+                        distance: 5,
+                    },
+                ),
+                (
+                    3,
+                    Solution {
+                        result: GameResult::AliceWins,
+                        distance: 1,
+                    },
+                ),
+            ]),
+            win_value: 1000.0,
+        };
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3, 4]));
+
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let mut statistics = Statistics::default();
+        let result = search_with_statistics(&tt, &evaluator, &generator, &state, 4, &Config::default(), &mut statistics);
+        assert_eq!(result.unwrap().id, 3);
+        assert_eq!(statistics.nodes, 2); // The root and 4
+        assert_eq!(statistics.oracle_hits, 2);
+
+        // The win is 2 plies from the root
+        assert_eq!(tt.borrow_mut().check(1, -1).unwrap().0, 998.0);
+    }
+
+    #[test]
+    fn test_oracle_draws_and_losses() {
+        let generator = MockResponseGenerator::new()
+            .add_state(MockGameState::new(2, PlayerId::BOB as u8).with_children(vec![4]))
+            .add_state(MockGameState::new(3, PlayerId::BOB as u8).with_children(vec![4]))
+            .add_state(MockGameState::new(4, PlayerId::ALICE as u8).with_value(0.0));
+        let evaluator = OracleEvaluator {
+            inner: MockStaticEvaluator::new().with_value(2, 50.0),
+            solutions: HashMap::from([
+                (
+                    2,
+                    Solution {
+                        result: GameResult::BobWins,
+                        distance: 3,
+                    },
+                ),
+                (
+                    3,
+                    Solution {
+                        result: GameResult::Draw,
+                        distance: 0,
+                    },
+                ),
+            ]),
+            win_value: 1000.0,
+        };
+
+        // Alice prefers a draw to a loss
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2, 3]));
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        assert_eq!(search(&tt, &evaluator, &generator, &state, 2).unwrap().id, 3);
+        assert_eq!(tt.borrow_mut().check(1, -1).unwrap().0, 0.0);
+
+        // Bob prefers a win to a draw
+        let state = Rc::new(MockGameState::new(1, PlayerId::BOB as u8).with_children(vec![2, 3]));
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        assert_eq!(search(&tt, &evaluator, &generator, &state, 2).unwrap().id, 2);
+        assert_eq!(tt.borrow_mut().check(1, -1).unwrap().0, -996.0);
+    }
+
+    #[test]
+    fn test_oracle_wins_reused_from_another_root() {
+        let generator = MockResponseGenerator::new()
+            .add_state(MockGameState::new(2, PlayerId::BOB as u8).with_children(vec![3]))
+            .add_state(MockGameState::new(3, PlayerId::ALICE as u8).with_children(vec![4, 5]))
+            .add_state(MockGameState::new(4, PlayerId::BOB as u8).with_children(vec![5]))
+            .add_state(MockGameState::new(5, PlayerId::BOB as u8).with_value(0.0))
+            .add_state(MockGameState::new(7, PlayerId::BOB as u8).with_children(vec![5]));
+        let evaluator = OracleEvaluator {
+            inner: MockStaticEvaluator::new(),
+            solutions: HashMap::from([
+                (
+                    4,
+                    Solution {
+                        result: GameResult::AliceWins,
+                        distance: 1,
+                    },
+                ),
+                (
+                    7,
+                    Solution {
+                        result: GameResult::AliceWins,
+                        distance: 1,
+                    },
+                ),
+            ]),
+            win_value: 1000.0,
+        };
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+
+        // The win is 4 plies from the first root and 2 plies from 3
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![2]));
+        let mut statistics = Statistics::default();
+        search_with_statistics(&tt, &evaluator, &generator, &state, 4, &Config::default(), &mut statistics);
+        assert_eq!(statistics.oracle_hits, 1);
+        assert_eq!(tt.borrow_mut().check(1, -1).unwrap().0, 996.0);
+
+        // From the second root, the value of 3 comes from the T-table and the win is 3 plies away. The win after 7 is quicker.
+        let state = Rc::new(MockGameState::new(6, PlayerId::BOB as u8).with_children(vec![3, 7]));
+        let mut statistics = Statistics::default();
+        let result = search_with_statistics(&tt, &evaluator, &generator, &state, 2, &Config::default(), &mut statistics);
+        assert_eq!(result.unwrap().id, 3);
+        assert_eq!(statistics.oracle_hits, 1);
+        assert_eq!(statistics.nodes, 1);
+        assert_eq!(tt.borrow_mut().check(6, -1).unwrap().0, 997.0);
+    }
+
+    #[test]
+    fn test_oracle_with_small_win_values() {
+        // The win values are too close to the draw value to reflect the number of plies to the end of the game
+        let generator = MockResponseGenerator::new()
+            .add_state(MockGameState::new(3, PlayerId::BOB as u8).with_children(vec![4]))
+            .add_state(MockGameState::new(4, PlayerId::BOB as u8).with_value(0.5));
+        let evaluator = OracleEvaluator {
+            inner: MockStaticEvaluator::new(),
+            solutions: HashMap::from([(
+                3,
+                Solution {
+                    result: GameResult::AliceWins,
+                    distance: 1,
+                },
+            )]),
+            win_value: 1.0,
+        };
+        let state = Rc::new(MockGameState::new(1, PlayerId::ALICE as u8).with_children(vec![3, 4]));
+
+        // Alice still chooses the win, and the value of 4 is stored unchanged
+        let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+        let result = search(&tt, &evaluator, &generator, &state, 2);
+        assert_eq!(result.unwrap().id, 3);
+        assert_eq!(tt.borrow_mut().check(1, -1).unwrap().0, 1.0);
+        assert_eq!(tt.borrow_mut().check(4, -1), Some((0.5, 0)));
+    }
 }
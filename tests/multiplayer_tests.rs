@@ -0,0 +1,206 @@
+//! Unit tests for the multi-player search implementations
+//!
+//! These tests use mock implementations of a 3-player game tree to verify the max^n and paranoid searches.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use game_player::minimax::ResponseGenerator;
+use game_player::multiplayer::{VectorEvaluator, max_n_search, paranoid_search};
+use game_player::state::State;
+
+/// Mock game state for testing
+#[derive(Debug, Clone, PartialEq)]
+struct MockGameState {
+    id: u32,
+    player: u8,
+    values: Vec<f32>,   // Values of the state for each player
+    children: Vec<u32>, // IDs of child states
+}
+
+impl MockGameState {
+    fn new(id: u32, player: u8) -> Self {
+        Self {
+            id,
+            player,
+            values: vec![0.0; 3],
+            children: Vec::new(),
+        }
+    }
+
+    fn with_values(mut self, values: [f32; 3]) -> Self {
+        self.values = values.to_vec();
+        self
+    }
+
+    fn with_children(mut self, children: Vec<u32>) -> Self {
+        self.children = children;
+        self
+    }
+}
+
+impl State for MockGameState {
+    type Action = u32;
+
+    fn fingerprint(&self) -> u64 {
+        self.id as u64
+    }
+
+    fn whose_turn(&self) -> u8 {
+        self.player
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    fn apply(&self, _action: &u32) -> Self {
+        self.clone()
+    }
+}
+
+/// Mock evaluator that returns the values stored in the state
+struct MockEvaluator;
+
+impl VectorEvaluator<MockGameState> for MockEvaluator {
+    fn evaluate(&self, state: &MockGameState) -> Vec<f32> {
+        state.values.clone()
+    }
+
+    fn player_wins_value(&self) -> f32 {
+        100.0
+    }
+}
+
+/// Mock response generator that creates predefined child states
+struct MockResponseGenerator {
+    states: HashMap<u32, MockGameState>,
+}
+
+impl MockResponseGenerator {
+    fn new(states: Vec<MockGameState>) -> Self {
+        Self {
+            states: states.into_iter().map(|state| (state.id, state)).collect(),
+        }
+    }
+
+    fn root(&self) -> Rc<MockGameState> {
+        Rc::new(self.states[&1].clone())
+    }
+}
+
+impl ResponseGenerator for MockResponseGenerator {
+    type State = MockGameState;
+
+    fn generate(&self, state: &Rc<MockGameState>, _depth: i32) -> Vec<Box<MockGameState>> {
+        state
+            .children
+            .iter()
+            .map(|id| Box::new(self.states[id].clone()))
+            .collect()
+    }
+}
+
+// Creates a tree in which player 0 chooses between 2 and 3, then player 1 chooses a leaf. In 2, player 1 prefers the leaf
+// that is good for player 0, but in 3, player 0 is better off if player 1 is hostile.
+fn divergent_tree() -> MockResponseGenerator {
+    MockResponseGenerator::new(vec![
+        MockGameState::new(1, 0).with_children(vec![2, 3]),
+        MockGameState::new(2, 1).with_children(vec![4, 5]),
+        MockGameState::new(3, 1).with_children(vec![6, 7]),
+        MockGameState::new(4, 2).with_values([5.0, 1.0, 4.0]),
+        MockGameState::new(5, 2).with_values([2.0, 0.0, 8.0]),
+        MockGameState::new(6, 2).with_values([3.0, 2.0, 5.0]),
+        MockGameState::new(7, 2).with_values([4.0, 3.0, 2.0]),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_moves() {
+        let generator = MockResponseGenerator::new(vec![MockGameState::new(1, 0)]);
+        let root = generator.root();
+
+        assert!(max_n_search(&MockEvaluator, &generator, &root, 3).is_none());
+        assert!(paranoid_search(&MockEvaluator, &generator, &root, 3).is_none());
+    }
+
+    #[test]
+    fn test_max_n_each_player_maximizes_own_value() {
+        let generator = divergent_tree();
+        let root = generator.root();
+
+        // Player 1 chooses 4 (1.0 > 0.0) after 2, and 7 (3.0 > 2.0) after 3. Player 0 gets 5.0 from 2 and 4.0 from 3.
+        let result = max_n_search(&MockEvaluator, &generator, &root, 2).unwrap();
+        assert_eq!(result.id, 2);
+    }
+
+    #[test]
+    fn test_paranoid_assumes_hostile_opponents() {
+        let generator = divergent_tree();
+        let root = generator.root();
+
+        // Player 1 is assumed to minimize player 0's value: 2.0 after 2 and 3.0 after 3
+        let result = paranoid_search(&MockEvaluator, &generator, &root, 2).unwrap();
+        assert_eq!(result.id, 3);
+    }
+
+    #[test]
+    fn test_depth_limit_uses_static_values() {
+        let generator = MockResponseGenerator::new(vec![
+            MockGameState::new(1, 0).with_children(vec![2, 3]),
+            MockGameState::new(2, 1).with_values([1.0, 0.0, 0.0]).with_children(vec![4]),
+            MockGameState::new(3, 1).with_values([2.0, 0.0, 0.0]).with_children(vec![5]),
+            MockGameState::new(4, 2).with_values([9.0, 0.0, 0.0]),
+            MockGameState::new(5, 2).with_values([0.0, 0.0, 0.0]),
+        ]);
+        let root = generator.root();
+
+        // At a depth of 1, only the static values of 2 and 3 are considered
+        assert_eq!(max_n_search(&MockEvaluator, &generator, &root, 1).unwrap().id, 3);
+        assert_eq!(paranoid_search(&MockEvaluator, &generator, &root, 1).unwrap().id, 3);
+
+        // At a depth of 2, the forced responses are considered
+        assert_eq!(max_n_search(&MockEvaluator, &generator, &root, 2).unwrap().id, 2);
+        assert_eq!(paranoid_search(&MockEvaluator, &generator, &root, 2).unwrap().id, 2);
+    }
+
+    #[test]
+    fn test_max_n_three_players_in_turn() {
+        // Player 0 -> player 1 -> player 2 -> leaves. Each player picks the leaf best for themselves.
+        let generator = MockResponseGenerator::new(vec![
+            MockGameState::new(1, 0).with_children(vec![2, 3]),
+            MockGameState::new(2, 1).with_children(vec![4, 5]),
+            MockGameState::new(3, 1).with_children(vec![6]),
+            MockGameState::new(4, 2).with_children(vec![7, 8]),
+            MockGameState::new(5, 2).with_children(vec![9]),
+            MockGameState::new(6, 2).with_children(vec![10]),
+            MockGameState::new(7, 0).with_values([9.0, 1.0, 1.0]),
+            MockGameState::new(8, 0).with_values([0.0, 5.0, 2.0]),
+            MockGameState::new(9, 0).with_values([8.0, 4.0, 0.0]),
+            MockGameState::new(10, 0).with_values([3.0, 3.0, 3.0]),
+        ]);
+        let root = generator.root();
+
+        // Player 2 picks 8 after 4 (2.0 > 1.0), so player 1 gets 5.0 from 4 and 4.0 from 5, and picks 4. Player 0 then gets
+        // 0.0 from 2 and 3.0 from 3.
+        let result = max_n_search(&MockEvaluator, &generator, &root, 3).unwrap();
+        assert_eq!(result.id, 3);
+    }
+
+    #[test]
+    fn test_win_stops_search() {
+        let generator = MockResponseGenerator::new(vec![
+            MockGameState::new(1, 0).with_children(vec![2, 3]),
+            MockGameState::new(2, 1).with_values([100.0, 0.0, 0.0]),
+            MockGameState::new(3, 1).with_values([50.0, 0.0, 0.0]),
+        ]);
+        let root = generator.root();
+
+        assert_eq!(max_n_search(&MockEvaluator, &generator, &root, 3).unwrap().id, 2);
+        assert_eq!(paranoid_search(&MockEvaluator, &generator, &root, 3).unwrap().id, 2);
+    }
+}
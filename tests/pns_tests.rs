@@ -0,0 +1,263 @@
+//! Unit tests for the proof-number search implementations
+//!
+//! These tests use small games with known results to verify the proof-number and df-pn searches.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use game_player::minimax::ResponseGenerator;
+use game_player::pns::{GameResult, Proof, ResultEvaluator, df_pn, prove, solve};
+use game_player::state::{PlayerId, State};
+
+/// A game of Nim with a single pile. Each player takes 1, 2 or 3 stones, and the player that takes the last stone wins. The
+/// player to move loses if and only if the number of stones is a multiple of 4.
+#[derive(Debug, Clone, PartialEq)]
+struct NimState {
+    stones: u32,
+    player: u8,
+}
+
+impl State for NimState {
+    type Action = u32;
+
+    fn fingerprint(&self) -> u64 {
+        (self.stones as u64) << 1 | self.player as u64
+    }
+
+    fn whose_turn(&self) -> u8 {
+        self.player
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.stones == 0
+    }
+
+    fn apply(&self, taken: &u32) -> Self {
+        Self {
+            stones: self.stones - taken,
+            player: 1 - self.player,
+        }
+    }
+}
+
+struct Nim;
+
+impl ResultEvaluator<NimState> for Nim {
+    fn result(&self, state: &NimState) -> GameResult {
+        // The player to move has no stones to take, so the other player took the last one
+        if state.player == PlayerId::ALICE as u8 { GameResult::BobWins } else { GameResult::AliceWins }
+    }
+}
+
+impl ResponseGenerator for Nim {
+    type State = NimState;
+
+    fn generate(&self, state: &Rc<NimState>, _depth: i32) -> Vec<Box<NimState>> {
+        (1..=state.stones.min(3)).map(|taken| Box::new(state.apply(&taken))).collect()
+    }
+}
+
+/// Tic-tac-toe. Alice plays X and moves first. The game is a draw with perfect play.
+#[derive(Debug, Clone, PartialEq)]
+struct TicTacToeState {
+    board: [u8; 9], // 0 = empty, 1 = Alice, 2 = Bob
+}
+
+const LINES: [[usize; 3]; 8] = [[0, 1, 2], [3, 4, 5], [6, 7, 8], [0, 3, 6], [1, 4, 7], [2, 5, 8], [0, 4, 8], [2, 4, 6]];
+
+impl TicTacToeState {
+    fn from(cells: &str) -> Self {
+        let mut board = [0; 9];
+        for (cell, c) in board.iter_mut().zip(cells.chars()) {
+            *cell = match c {
+                'X' => 1,
+                'O' => 2,
+                _ => 0,
+            };
+        }
+        Self { board }
+    }
+
+    fn winner(&self) -> Option<u8> {
+        LINES
+            .iter()
+            .find(|line| self.board[line[0]] != 0 && line.iter().all(|&i| self.board[i] == self.board[line[0]]))
+            .map(|line| self.board[line[0]])
+    }
+}
+
+impl State for TicTacToeState {
+    type Action = usize;
+
+    fn fingerprint(&self) -> u64 {
+        self.board.iter().fold(0, |fingerprint, &cell| fingerprint * 3 + cell as u64)
+    }
+
+    fn whose_turn(&self) -> u8 {
+        let empty = self.board.iter().filter(|&&cell| cell == 0).count();
+        if empty % 2 == 1 { PlayerId::ALICE as u8 } else { PlayerId::BOB as u8 }
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.winner().is_some() || self.board.iter().all(|&cell| cell != 0)
+    }
+
+    fn apply(&self, cell: &usize) -> Self {
+        let mut next = self.clone();
+        next.board[*cell] = self.whose_turn() + 1;
+        next
+    }
+}
+
+struct TicTacToe;
+
+impl ResultEvaluator<TicTacToeState> for TicTacToe {
+    fn result(&self, state: &TicTacToeState) -> GameResult {
+        match state.winner() {
+            Some(1) => GameResult::AliceWins,
+            Some(_) => GameResult::BobWins,
+            None => GameResult::Draw,
+        }
+    }
+}
+
+impl ResponseGenerator for TicTacToe {
+    type State = TicTacToeState;
+
+    fn generate(&self, state: &Rc<TicTacToeState>, _depth: i32) -> Vec<Box<TicTacToeState>> {
+        if state.is_terminal() {
+            return Vec::new();
+        }
+        (0..9).filter(|&cell| state.board[cell] == 0).map(|cell| Box::new(state.apply(&cell))).collect()
+    }
+}
+
+/// A game defined by a graph of states, which may contain cycles
+#[derive(Debug, Clone, PartialEq)]
+struct GraphState {
+    id: u32,
+    player: u8,
+}
+
+impl State for GraphState {
+    type Action = u32;
+
+    fn fingerprint(&self) -> u64 {
+        self.id as u64
+    }
+
+    fn whose_turn(&self) -> u8 {
+        self.player
+    }
+
+    fn is_terminal(&self) -> bool {
+        false
+    }
+
+    fn apply(&self, _action: &u32) -> Self {
+        self.clone()
+    }
+}
+
+/// The edges of the graph and the results of states with no responses
+struct Graph {
+    edges: HashMap<u32, Vec<u32>>,
+    results: HashMap<u32, GameResult>,
+}
+
+impl Graph {
+    fn state(id: u32) -> GraphState {
+        GraphState { id, player: (id % 2) as u8 }
+    }
+}
+
+impl ResultEvaluator<GraphState> for Graph {
+    fn result(&self, state: &GraphState) -> GameResult {
+        self.results[&state.id]
+    }
+}
+
+impl ResponseGenerator for Graph {
+    type State = GraphState;
+
+    fn generate(&self, state: &Rc<GraphState>, _depth: i32) -> Vec<Box<GraphState>> {
+        self.edges
+            .get(&state.id)
+            .map(|children| children.iter().map(|&id| Box::new(Graph::state(id))).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nim(stones: u32) -> Rc<NimState> {
+        Rc::new(NimState {
+            stones,
+            player: PlayerId::ALICE as u8,
+        })
+    }
+
+    #[test]
+    fn test_terminal_state() {
+        let state = nim(0);
+        assert_eq!(prove(&Nim, &Nim, &state, PlayerId::BOB, 100), Proof::Proven);
+        assert_eq!(df_pn(&Nim, &Nim, &state, PlayerId::ALICE, 100), Proof::Disproven);
+        assert_eq!(solve(&Nim, &Nim, &state, 100), Some(GameResult::BobWins));
+    }
+
+    #[test]
+    fn test_nim() {
+        for stones in 1..=20 {
+            let state = nim(stones);
+            let alice_wins = stones % 4 != 0;
+            let expected = if alice_wins { Proof::Proven } else { Proof::Disproven };
+            assert_eq!(prove(&Nim, &Nim, &state, PlayerId::ALICE, 1000000), expected, "{stones} stones");
+            assert_eq!(df_pn(&Nim, &Nim, &state, PlayerId::ALICE, 1000000), expected, "{stones} stones");
+
+            let expected = if alice_wins { GameResult::AliceWins } else { GameResult::BobWins };
+            assert_eq!(solve(&Nim, &Nim, &state, 1000000), Some(expected), "{stones} stones");
+        }
+    }
+
+    #[test]
+    fn test_tic_tac_toe_is_a_draw() {
+        let state = Rc::new(TicTacToeState::from("........."));
+        assert_eq!(df_pn(&TicTacToe, &TicTacToe, &state, PlayerId::ALICE, 1000000), Proof::Disproven);
+        assert_eq!(solve(&TicTacToe, &TicTacToe, &state, 1000000), Some(GameResult::Draw));
+    }
+
+    #[test]
+    fn test_tic_tac_toe_forced_win() {
+        // Alice completes the diagonal through the center
+        let state = Rc::new(TicTacToeState::from("X.O..O..X"));
+        assert_eq!(prove(&TicTacToe, &TicTacToe, &state, PlayerId::ALICE, 100000), Proof::Proven);
+        assert_eq!(solve(&TicTacToe, &TicTacToe, &state, 100000), Some(GameResult::AliceWins));
+
+        // Bob completes a line on his move
+        let state = Rc::new(TicTacToeState::from("XX.OO.X.."));
+        assert_eq!(solve(&TicTacToe, &TicTacToe, &state, 100000), Some(GameResult::BobWins));
+    }
+
+    #[test]
+    fn test_limit() {
+        let state = Rc::new(TicTacToeState::from("........."));
+        assert_eq!(prove(&TicTacToe, &TicTacToe, &state, PlayerId::ALICE, 10), Proof::Unknown);
+        assert_eq!(df_pn(&TicTacToe, &TicTacToe, &state, PlayerId::ALICE, 10), Proof::Unknown);
+        assert_eq!(solve(&TicTacToe, &TicTacToe, &state, 10), None);
+    }
+
+    #[test]
+    fn test_repetition_does_not_achieve_goal() {
+        // Alice can only move from 0 to 1. Bob can return to 0, or move to 3, which loses.
+        let graph = Graph {
+            edges: HashMap::from([(0, vec![1]), (1, vec![0, 3])]),
+            results: HashMap::from([(3, GameResult::AliceWins)]),
+        };
+        let state = Rc::new(Graph::state(0));
+        assert_eq!(df_pn(&graph, &graph, &state, PlayerId::ALICE, 1000), Proof::Disproven);
+        assert_eq!(df_pn(&graph, &graph, &state, PlayerId::BOB, 1000), Proof::Disproven);
+        assert_eq!(solve(&graph, &graph, &state, 1000), Some(GameResult::Draw));
+    }
+}
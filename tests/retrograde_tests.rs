@@ -0,0 +1,336 @@
+//! Unit tests for the retrograde analysis implementation
+//!
+//! These tests generate tablebases of small games with known results.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use game_player::minimax::{self, ResponseGenerator};
+use game_player::pns::{GameResult, ResultEvaluator};
+use game_player::retrograde::{Solution, StateSpace, Tablebase, TablebaseOracle};
+use game_player::state::{PlayerId, State};
+use game_player::static_evaluator::{Oracle, StaticEvaluator};
+use game_player::transposition_table::TranspositionTable;
+
+const MAX_STONES: u32 = 20;
+
+/// A game of Nim with a single pile. Each player takes 1, 2 or 3 stones, and the player that takes the last stone wins.
+#[derive(Debug, Clone, PartialEq)]
+struct NimState {
+    stones: u32,
+    player: u8,
+}
+
+impl State for NimState {
+    type Action = u32;
+
+    fn fingerprint(&self) -> u64 {
+        (self.stones as u64) << 1 | self.player as u64
+    }
+
+    fn whose_turn(&self) -> u8 {
+        self.player
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.stones == 0
+    }
+
+    fn apply(&self, taken: &u32) -> Self {
+        Self {
+            stones: self.stones - taken,
+            player: 1 - self.player,
+        }
+    }
+}
+
+struct Nim;
+
+impl ResultEvaluator<NimState> for Nim {
+    fn result(&self, state: &NimState) -> GameResult {
+        // The player to move has no stones to take, so the other player took the last one
+        if state.player == PlayerId::ALICE as u8 { GameResult::BobWins } else { GameResult::AliceWins }
+    }
+}
+
+impl ResponseGenerator for Nim {
+    type State = NimState;
+
+    fn generate(&self, state: &Rc<NimState>, _depth: i32) -> Vec<Box<NimState>> {
+        (1..=state.stones.min(3)).map(|taken| Box::new(state.apply(&taken))).collect()
+    }
+}
+
+impl StateSpace for Nim {
+    type State = NimState;
+
+    fn size(&self) -> usize {
+        (MAX_STONES as usize + 1) * 2
+    }
+
+    fn index(&self, state: &NimState) -> usize {
+        state.stones as usize * 2 + state.player as usize
+    }
+
+    fn state(&self, index: usize) -> Option<NimState> {
+        Some(NimState {
+            stones: (index / 2) as u32,
+            player: (index % 2) as u8,
+        })
+    }
+
+    fn predecessors(&self, state: &NimState) -> Vec<NimState> {
+        (state.stones + 1..=(state.stones + 3).min(MAX_STONES))
+            .map(|stones| NimState {
+                stones,
+                player: 1 - state.player,
+            })
+            .collect()
+    }
+
+    fn contains(&self, state: &NimState) -> bool {
+        state.stones <= MAX_STONES
+    }
+}
+
+/// An evaluator for Nim that knows nothing, except for the results in its tablebase
+struct NimEvaluator {
+    oracle: TablebaseOracle<Nim>,
+}
+
+impl StaticEvaluator<NimState> for NimEvaluator {
+    fn evaluate(&self, _state: &NimState) -> f32 {
+        0.0
+    }
+
+    fn alice_wins_value(&self) -> f32 {
+        100.0
+    }
+
+    fn bob_wins_value(&self) -> f32 {
+        -100.0
+    }
+
+    fn oracle(&self) -> Option<&dyn Oracle<NimState>> {
+        Some(&self.oracle)
+    }
+}
+
+/// A game defined by a graph of states, which may contain cycles. The player to move is the parity of the ID.
+#[derive(Debug, Clone, PartialEq)]
+struct GraphState {
+    id: u32,
+}
+
+impl State for GraphState {
+    type Action = u32;
+
+    fn fingerprint(&self) -> u64 {
+        self.id as u64
+    }
+
+    fn whose_turn(&self) -> u8 {
+        (self.id % 2) as u8
+    }
+
+    fn is_terminal(&self) -> bool {
+        false
+    }
+
+    fn apply(&self, _action: &u32) -> Self {
+        self.clone()
+    }
+}
+
+/// The edges of the graph, the results of states with no responses, and the number of IDs
+struct Graph {
+    edges: HashMap<u32, Vec<u32>>,
+    results: HashMap<u32, GameResult>,
+    size: usize,
+}
+
+impl ResultEvaluator<GraphState> for Graph {
+    fn result(&self, state: &GraphState) -> GameResult {
+        self.results[&state.id]
+    }
+}
+
+impl ResponseGenerator for Graph {
+    type State = GraphState;
+
+    fn generate(&self, state: &Rc<GraphState>, _depth: i32) -> Vec<Box<GraphState>> {
+        self.edges
+            .get(&state.id)
+            .map(|children| children.iter().map(|&id| Box::new(GraphState { id })).collect())
+            .unwrap_or_default()
+    }
+}
+
+impl StateSpace for Graph {
+    type State = GraphState;
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn index(&self, state: &GraphState) -> usize {
+        state.id as usize
+    }
+
+    fn state(&self, index: usize) -> Option<GraphState> {
+        let id = index as u32;
+        (self.edges.contains_key(&id) || self.results.contains_key(&id)).then_some(GraphState { id })
+    }
+
+    fn predecessors(&self, state: &GraphState) -> Vec<GraphState> {
+        self.edges
+            .iter()
+            .filter(|(_, children)| children.contains(&state.id))
+            .map(|(&id, _)| GraphState { id })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nim_results_and_distances() {
+        let tablebase = Tablebase::generate(&Nim, &Nim, &Nim);
+        assert_eq!(tablebase.len(), Nim.size());
+
+        for stones in 0..=MAX_STONES {
+            let state = NimState {
+                stones,
+                player: PlayerId::ALICE as u8,
+            };
+            let solution = tablebase.probe_state(&Nim, &state).unwrap();
+
+            // Alice loses if the number of stones is a multiple of 4. The winner leaves a multiple of 4 on every move.
+            let expected = if stones % 4 == 0 {
+                Solution {
+                    result: GameResult::BobWins,
+                    distance: (stones / 2) as u16,
+                }
+            } else {
+                Solution {
+                    result: GameResult::AliceWins,
+                    distance: (stones / 4 * 2 + 1) as u16,
+                }
+            };
+            assert_eq!(solution, expected, "{stones} stones");
+
+            // The results are the same with Bob to move, but the winner is reversed
+            let state = NimState {
+                stones,
+                player: PlayerId::BOB as u8,
+            };
+            let solution = tablebase.probe_state(&Nim, &state).unwrap();
+            assert_eq!(solution.distance, expected.distance);
+            assert_ne!(solution.result, expected.result);
+        }
+    }
+
+    #[test]
+    fn test_draws_and_invalid_indexes() {
+        // Alice can only move from 0 to 1. Bob can return to 0, or move to 3, which loses. From 4, Alice can move to 3 or 5,
+        // which is a draw. There is no state 2.
+        let graph = Graph {
+            edges: HashMap::from([(0, vec![1]), (1, vec![0, 3]), (4, vec![3, 5])]),
+            results: HashMap::from([(3, GameResult::AliceWins), (5, GameResult::Draw)]),
+            size: 6,
+        };
+        let tablebase = Tablebase::generate(&graph, &graph, &graph);
+
+        let draw = Some(Solution {
+            result: GameResult::Draw,
+            distance: 0,
+        });
+        assert_eq!(tablebase.probe(0), draw);
+        assert_eq!(tablebase.probe(1), draw);
+        assert_eq!(tablebase.probe(2), None);
+        assert_eq!(
+            tablebase.probe(3),
+            Some(Solution {
+                result: GameResult::AliceWins,
+                distance: 0
+            })
+        );
+        assert_eq!(
+            tablebase.probe(4),
+            Some(Solution {
+                result: GameResult::AliceWins,
+                distance: 1
+            })
+        );
+        assert_eq!(tablebase.probe(5), draw);
+        assert_eq!(tablebase.probe(6), None);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let tablebase = Tablebase::generate(&Nim, &Nim, &Nim);
+        let path = std::env::temp_dir().join(format!("game_player_retrograde_{}.tb", std::process::id()));
+
+        tablebase.save(&path).unwrap();
+        let loaded = Tablebase::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), tablebase);
+
+        // The file holds a 16-byte header and 2 bytes per entry
+        let mut bytes = Vec::new();
+        tablebase.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 16 + 2 * tablebase.len());
+    }
+
+    #[test]
+    fn test_load_rejects_invalid_data() {
+        let tablebase = Tablebase::generate(&Nim, &Nim, &Nim);
+        let mut bytes = Vec::new();
+        tablebase.write_to(&mut bytes).unwrap();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(Tablebase::read_from(&mut bad_magic.as_slice()).is_err());
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 99;
+        assert!(Tablebase::read_from(&mut bad_version.as_slice()).is_err());
+
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(Tablebase::read_from(&mut &truncated[..]).is_err());
+
+        // A count whose size overflows is rejected rather than wrapped
+        let mut huge = bytes.clone();
+        huge[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+        let error = Tablebase::read_from(&mut huge.as_slice()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_search_with_tablebase() {
+        let evaluator = NimEvaluator {
+            oracle: TablebaseOracle::new(Nim, Tablebase::generate(&Nim, &Nim, &Nim)),
+        };
+
+        // Alice leaves a multiple of 4 stones. A search of 1 ply finds it because the tablebase knows the results.
+        for stones in [7, 22] {
+            let state = Rc::new(NimState {
+                stones,
+                player: PlayerId::ALICE as u8,
+            });
+            let tt = Rc::new(RefCell::new(TranspositionTable::new(1000, 100)));
+            let response = minimax::search(&tt, &evaluator, &Nim, &state, 1).unwrap();
+            assert_eq!(response.stones % 4, 0, "{stones} stones");
+        }
+
+        // States that are not in the space are not probed
+        let state = NimState {
+            stones: MAX_STONES + 1,
+            player: PlayerId::ALICE as u8,
+        };
+        assert_eq!(evaluator.oracle.probe(&state), None);
+    }
+}